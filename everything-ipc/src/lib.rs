@@ -82,11 +82,8 @@ impl IpcWindow {
         const EVERYTHING_IPC_GET_MINOR_VERSION: u32 = 1;
         const EVERYTHING_IPC_GET_REVISION: u32 = 2;
         const EVERYTHING_IPC_GET_BUILD_NUMBER: u32 = 3;
-        // const EVERYTHING_IPC_GET_TARGET_MACHINE: u32 = 5;
 
-        let send_u32 = |command: u32| unsafe {
-            SendMessageW(self.hwnd, EVERYTHING_WM_IPC, command as usize, 0)
-        } as u32;
+        let send_u32 = |command: u32| self.send_u32(command);
 
         Version {
             major: send_u32(EVERYTHING_IPC_GET_MAJOR_VERSION),
@@ -95,6 +92,20 @@ impl IpcWindow {
             build: send_u32(EVERYTHING_IPC_GET_BUILD_NUMBER),
         }
     }
+
+    /// The same as [`Self::get_version()`], together with the machine type Everything is built for.
+    pub fn get_version_with_target_machine(&self) -> (Version, TargetMachine) {
+        const EVERYTHING_IPC_GET_TARGET_MACHINE: u32 = 5;
+
+        (
+            self.get_version(),
+            self.send_u32(EVERYTHING_IPC_GET_TARGET_MACHINE).into(),
+        )
+    }
+
+    fn send_u32(&self, command: u32) -> u32 {
+        unsafe { SendMessageW(self.hwnd, EVERYTHING_WM_IPC, command as usize, 0) as u32 }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -115,3 +126,35 @@ impl Version {
         }
     }
 }
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.revision, self.build
+        )
+    }
+}
+
+/// The machine type Everything is built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TargetMachine {
+    Unknown,
+    X86,
+    X64,
+    Arm,
+    Arm64,
+}
+
+impl From<u32> for TargetMachine {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::X86,
+            2 => Self::X64,
+            3 => Self::Arm,
+            4 => Self::Arm64,
+            _ => Self::Unknown,
+        }
+    }
+}
@@ -0,0 +1,830 @@
+//! `#[derive(OptionsForm)]` and `#[derive(OptionsEnum)]`, see `everything_plugin::ui::winio::form`.
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Expr, Fields, Ident, LitStr, Type, Visibility, spanned::Spanned,
+};
+
+const ATTR: &str = "options_form";
+
+const NUMBERS: [&str; 14] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32",
+    "f64",
+];
+
+enum Kind {
+    /// `CheckBox`
+    Check,
+    /// `Edit`
+    Text,
+    /// `Edit`, parsed
+    Number,
+    /// `ComboBox`, with `OptionsEnum`
+    Combo,
+}
+
+impl Kind {
+    /// `None` if the widget can't be known from the type, e.g. `Option<T>`, `PathBuf`, an enum or a type alias.
+    fn of(ty: &Type) -> Option<Self> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        let segment = path.path.segments.last()?;
+        if path.qself.is_some() || !segment.arguments.is_empty() {
+            return None;
+        }
+        match segment.ident.to_string().as_str() {
+            "bool" => Some(Self::Check),
+            "String" => Some(Self::Text),
+            ident if NUMBERS.contains(&ident) => Some(Self::Number),
+            _ => None,
+        }
+    }
+}
+
+struct Field {
+    ident: Ident,
+    ty: Type,
+    kind: Kind,
+    label: TokenStream,
+    help: Option<Expr>,
+    group: Option<Expr>,
+    enabled_by: Option<Ident>,
+}
+
+impl Field {
+    fn widget(&self) -> Ident {
+        format_ident!("field_{}", self.ident)
+    }
+
+    /// `None` for `CheckBox`, which has its own text.
+    fn label(&self) -> Option<Ident> {
+        match self.kind {
+            Kind::Check => None,
+            _ => Some(format_ident!("label_{}", self.ident)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FormArgs {
+    app: Option<Type>,
+    handler: Option<Expr>,
+    name: Option<Ident>,
+}
+
+fn parse_form_args(attrs: &[Attribute]) -> syn::Result<FormArgs> {
+    let mut args = FormArgs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident(ATTR)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("app") {
+                args.app = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("handler") {
+                args.handler = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("name") {
+                args.name = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected `app`, `handler` or `name`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(args)
+}
+
+/// `None` if skipped.
+fn parse_field(field: &syn::Field) -> syn::Result<Option<Field>> {
+    let ident = field.ident.clone().expect("named field");
+    let mut label = None;
+    let mut help = None;
+    let mut group = None;
+    let mut enabled_by = None;
+    let mut skip = false;
+    let mut combo = false;
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident(ATTR)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("label") {
+                label = Some(meta.value()?.parse::<Expr>()?);
+            } else if meta.path.is_ident("help") {
+                help = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("group") {
+                group = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("enabled_by") {
+                enabled_by = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("combo") {
+                combo = true;
+            } else {
+                return Err(meta
+                    .error("expected `label`, `help`, `group`, `enabled_by`, `combo` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    if skip {
+        return Ok(None);
+    }
+
+    let kind = match (combo, Kind::of(&field.ty)) {
+        (true, _) => Kind::Combo,
+        (false, Some(kind)) => kind,
+        (false, None) => {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                format!(
+                    "unsupported type of field `{ident}`, expected `bool`, `String` or a number. Use `#[options_form(combo)]` for an `OptionsEnum`, or `#[options_form(skip)]` to keep it as is"
+                ),
+            ));
+        }
+    };
+
+    let label = match label {
+        Some(label) => label.into_token_stream(),
+        None => default_label(&ident).into_token_stream(),
+    };
+    Ok(Some(Field {
+        kind,
+        ty: field.ty.clone(),
+        ident,
+        label,
+        help,
+        group,
+        enabled_by,
+    }))
+}
+
+/// `max_count` -> `"Max count"`
+fn default_label(ident: &Ident) -> LitStr {
+    let name = ident.to_string();
+    let name = name.strip_prefix("r#").unwrap_or(&name).replace('_', " ");
+    let mut chars = name.trim().chars();
+    let label = match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+    LitStr::new(&label, ident.span())
+}
+
+pub fn expand_options_form(input: DeriveInput) -> syn::Result<TokenStream> {
+    let config = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[derive(OptionsForm)] does not support generics",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "#[derive(OptionsForm)] must be put on a struct with named fields",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "#[derive(OptionsForm)] must be put on a struct with named fields",
+        ));
+    };
+
+    let args = parse_form_args(&input.attrs)?;
+    let Some(app) = args.app else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "missing `#[options_form(app = App)]`",
+        ));
+    };
+    let handler = args
+        .handler
+        .map(ToTokens::into_token_stream)
+        .unwrap_or_else(|| quote! { <#app>::handler() });
+    let form = args.name.unwrap_or_else(|| format_ident!("{config}Form"));
+    let message = format_ident!("{form}Message");
+    // User items are resolved outside of the module, as the winio prelude also has e.g. `App`
+    let app_alias = format_ident!("__{form}App");
+    let mut scoped = Vec::new();
+    let mut scoped_text = |name: Ident, text: &dyn ToTokens| {
+        scoped.push(quote! {
+            fn #name() -> ::std::string::String {
+                ::std::string::ToString::to_string(&#text)
+            }
+        });
+        quote! { super::#config::#name() }
+    };
+    let module = format_ident!(
+        "__{}_options_form",
+        config.to_string().to_lowercase(),
+        span = Span::call_site()
+    );
+
+    let mut fields = Vec::new();
+    for field in &named.named {
+        if let Some(field) = parse_field(field)? {
+            fields.push(field);
+        }
+    }
+    check_enabled_by(&fields)?;
+
+    // Ungrouped fields first, then groups in order of first appearance
+    let mut groups: Vec<(String, &Expr, Vec<&Field>)> = Vec::new();
+    let mut ungrouped = Vec::new();
+    for field in &fields {
+        match &field.group {
+            Some(group) => {
+                let key = group.to_token_stream().to_string();
+                match groups.iter_mut().find(|(k, _, _)| *k == key) {
+                    Some((_, _, members)) => members.push(field),
+                    None => groups.push((key, group, vec![field])),
+                }
+            }
+            None => ungrouped.push(field),
+        }
+    }
+    let group_idents: Vec<Ident> = (0..groups.len())
+        .map(|i| format_ident!("group_{i}"))
+        .collect();
+
+    // Struct fields and init()
+    let mut members = Vec::new();
+    let mut inits = Vec::new();
+    let mut member_idents = Vec::new();
+    let mut combo_labels = Vec::new();
+    for field in &fields {
+        let ident = &field.ident;
+        let widget = field.widget();
+        let label = scoped_text(format_ident!("__options_form_label_{ident}"), &field.label);
+        let ty = &field.ty;
+        if let Some(label_ident) = field.label() {
+            members.push(quote! { #label_ident: Child<Label>, });
+            inits.push(quote! {
+                let mut #label_ident = Child::<Label>::init(&window);
+                #label_ident.set_text(#label);
+            });
+            member_idents.push(label_ident);
+        }
+        let (widget_ty, init) = match field.kind {
+            Kind::Check => (quote! { CheckBox }, quote! { #widget.set_text(#label); }),
+            Kind::Text | Kind::Number => (quote! { Edit }, quote! {}),
+            Kind::Combo => (quote! { ComboBox }, {
+                let labels = format_ident!("__options_form_labels_{ident}");
+                combo_labels.push(quote! {
+                    fn #labels() -> ::std::vec::Vec<::std::string::String> {
+                        <#ty as ::everything_plugin::ui::winio::form::OptionsEnum>::labels()
+                    }
+                });
+                quote! {
+                    for (i, label) in super::#config::#labels().into_iter().enumerate() {
+                        #widget.insert(i, label);
+                    }
+                }
+            }),
+        };
+        members.push(quote! { #widget: Child<#widget_ty>, });
+        let help = field.help.as_ref().map(|help| {
+            let help = scoped_text(format_ident!("__options_form_help_{ident}"), help);
+            quote! { tooltip.add(&*#widget, #help); }
+        });
+        inits.push(quote! {
+            let mut #widget = Child::<#widget_ty>::init(&window);
+            #init
+            #help
+        });
+        member_idents.push(widget);
+    }
+    for ((_, group, _), ident) in groups.iter().zip(&group_idents) {
+        let group = scoped_text(format_ident!("__options_form_{ident}"), *group);
+        members.push(quote! { #ident: Child<Label>, });
+        inits.push(quote! {
+            let mut #ident = Child::<Label>::init(&window);
+            #ident.set_text(#group);
+        });
+        member_idents.push(ident.clone());
+    }
+
+    scoped.extend(combo_labels);
+
+    // set_config()
+    let set_config = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let widget = field.widget();
+        match field.kind {
+            Kind::Check => quote! { self.#widget.set_checked(config.#ident); },
+            Kind::Text => quote! { self.#widget.set_text(&config.#ident); },
+            Kind::Number => quote! { self.#widget.set_text(config.#ident.to_string()); },
+            Kind::Combo => quote! {
+                self.#widget.set_selection(Some(
+                    ::everything_plugin::ui::winio::form::OptionsEnum::index(&config.#ident),
+                ));
+            },
+        }
+    });
+
+    // Save: parse all fields before changing the config. Types are inferred from the assignments.
+    let mut parses = Vec::new();
+    let mut assigns = Vec::new();
+    for field in &fields {
+        let ident = &field.ident;
+        let widget = field.widget();
+        let value = format_ident!("value_{ident}");
+        let name = ident.to_string();
+        parses.push(match field.kind {
+            Kind::Check => quote! { let #value = Some(self.#widget.is_checked()); },
+            Kind::Text => quote! { let #value = Some(self.#widget.text()); },
+            Kind::Number => quote! {
+                let #value = match ::everything_plugin::ui::winio::form::parse_number(
+                    #name,
+                    &self.#widget.text(),
+                ) {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        errors.push(e);
+                        None
+                    }
+                };
+            },
+            Kind::Combo => quote! {
+                let #value = self
+                    .#widget
+                    .selection()
+                    .and_then(::everything_plugin::ui::winio::form::OptionsEnum::from_index);
+            },
+        });
+        assigns.push(quote! {
+            if let Some(value) = #value {
+                config.#ident = value;
+            }
+        });
+    }
+
+    // Enable dependencies, in declaration order
+    let toggles = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let widget = field.widget();
+        let enabled = format_ident!("enabled_{ident}");
+        let active = format_ident!("active_{ident}");
+        let (enabled_expr, set_enabled) = match &field.enabled_by {
+            Some(by) => {
+                let by = format_ident!("active_{by}");
+                let label = field
+                    .label()
+                    .map(|label| quote! { self.#label.set_enabled(#enabled); });
+                (
+                    quote! { #by },
+                    quote! {
+                        self.#widget.set_enabled(#enabled);
+                        #label
+                    },
+                )
+            }
+            None => (quote! { true }, quote! {}),
+        };
+        let active = match field.kind {
+            Kind::Check => quote! { let #active = #enabled && self.#widget.is_checked(); },
+            _ => quote! {},
+        };
+        quote! {
+            let #enabled = #enabled_expr;
+            #set_enabled
+            #active
+        }
+    });
+
+    let start_entries = fields
+        .iter()
+        .filter(|field| matches!(field.kind, Kind::Check))
+        .map(|field| {
+            let widget = field.widget();
+            quote! {
+                self.#widget => {
+                    CheckBoxEvent::Click => #message::Toggle,
+                }
+            }
+        });
+
+    // render()
+    let mut rows = Vec::new();
+    let mut layout_entries = Vec::new();
+    let mut push_field = |field: &Field, rows: &mut Vec<&str>| {
+        let row = Literal::usize_unsuffixed(rows.len());
+        let widget = field.widget();
+        match field.label() {
+            Some(label) => layout_entries.push(quote! {
+                self.#label => { column: 0, row: #row, margin: m_l, valign: VAlign::Center },
+                self.#widget => { column: 1, row: #row, margin: m },
+            }),
+            None => layout_entries.push(quote! {
+                self.#widget => { column: 0, row: #row, margin: m },
+            }),
+        }
+        rows.push("auto");
+    };
+    for field in &ungrouped {
+        push_field(field, &mut rows);
+    }
+    let mut group_entries = Vec::new();
+    for ((_, _, group_fields), ident) in groups.iter().zip(&group_idents) {
+        let row = Literal::usize_unsuffixed(rows.len());
+        group_entries.push(quote! {
+            self.#ident => { column: 0, row: #row, margin: m_g },
+        });
+        rows.push("auto");
+        for field in group_fields {
+            push_field(field, &mut rows);
+        }
+    }
+    rows.push("1*");
+    let rows = rows.join(",");
+
+    let vis = &input.vis;
+    let doc = format!(
+        "Options page generated by `#[derive(OptionsForm)]` for [`{config}`].\n\nUse with `ui::winio::spawn::<{form}>`."
+    );
+    let reexport_vis = match vis {
+        // `pub(super)` etc. are relative to the struct's module
+        Visibility::Inherited => quote! {},
+        vis => vis.to_token_stream(),
+    };
+
+    Ok(quote! {
+        #[doc(hidden)]
+        type #app_alias = #app;
+
+        #[doc(hidden)]
+        impl #config {
+            fn __options_form_with_config(f: impl FnOnce(&Self)) {
+                #handler.with_config(f)
+            }
+
+            #(#scoped)*
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_variables)]
+        mod #module {
+            use ::everything_plugin::ui::winio::prelude::*;
+
+            #[doc = #doc]
+            pub struct #form {
+                window: Child<Window>,
+                #[allow(dead_code)]
+                tooltip: ::everything_plugin::ui::winio::form::Tooltip,
+                #(#members)*
+            }
+
+            #[derive(Debug)]
+            pub enum #message {
+                Noop,
+                Close,
+                Redraw,
+                Toggle,
+                OptionsPage(OptionsPageMessage<super::#app_alias>),
+            }
+
+            impl From<OptionsPageMessage<super::#app_alias>> for #message {
+                fn from(value: OptionsPageMessage<super::#app_alias>) -> Self {
+                    Self::OptionsPage(value)
+                }
+            }
+
+            impl #form {
+                pub fn set_config(&mut self, config: &super::#config) {
+                    #(#set_config)*
+                }
+
+                fn toggle(&mut self) {
+                    #(#toggles)*
+                }
+            }
+
+            impl Component for #form {
+                type Event = ();
+                type Init<'a> = OptionsPageInit<'a, super::#app_alias>;
+                type Message = #message;
+
+                fn init(mut init: Self::Init<'_>, sender: &ComponentSender<Self>) -> Self {
+                    let mut window = init.window(sender);
+                    let tooltip = ::everything_plugin::ui::winio::form::Tooltip::new(&window);
+
+                    #(#inits)*
+
+                    let mut form = Self {
+                        window,
+                        tooltip,
+                        #(#member_idents,)*
+                    };
+                    super::#config::__options_form_with_config(|config| form.set_config(config));
+                    form.toggle();
+
+                    form.window.show();
+
+                    form
+                }
+
+                async fn start(&mut self, sender: &ComponentSender<Self>) -> ! {
+                    start! {
+                        sender, default: #message::Noop,
+                        self.window => {
+                            WindowEvent::Close => #message::Close,
+                            WindowEvent::Resize => #message::Redraw,
+                        }
+                        #(, #start_entries)*
+                    }
+                }
+
+                async fn update(&mut self, message: Self::Message, sender: &ComponentSender<Self>) -> bool {
+                    self.window.update().await;
+                    match message {
+                        #message::Noop => false,
+                        #message::Close => {
+                            sender.output(());
+                            false
+                        }
+                        #message::Redraw => true,
+                        #message::Toggle => {
+                            self.toggle();
+                            false
+                        }
+                        #message::OptionsPage(m) => {
+                            match m {
+                                OptionsPageMessage::Save(config, tx) => {
+                                    let mut errors = Vec::new();
+                                    #(#parses)*
+                                    if errors.is_empty() {
+                                        #(#assigns)*
+                                        tx.send(config).unwrap()
+                                    } else {
+                                        // The config is unchanged if `tx` is dropped
+                                        drop(tx);
+                                        ::everything_plugin::ui::winio::form::show_errors(&self.window, &errors).await;
+                                    }
+                                }
+                                OptionsPageMessage::Invalid(errors) => {
+                                    ::everything_plugin::ui::winio::form::show_errors(&self.window, &errors).await;
+                                }
+                            }
+                            false
+                        }
+                    }
+                }
+
+                fn render(&mut self, _sender: &ComponentSender<Self>) {
+                    self.window.render();
+
+                    let csize = self.window.client_size();
+
+                    let m = Margin::new(5., 0., 5., 0.);
+                    let m_l = Margin::new(0., 5., 0., 0.);
+                    let m_g = Margin::new(10., 0., 5., 0.);
+
+                    let mut grid = layout! {
+                        Grid::from_str("auto,1*", #rows).unwrap(),
+                        #(#layout_entries)*
+                        #(#group_entries)*
+                    };
+                    grid.set_size(csize);
+                }
+            }
+        }
+
+        #reexport_vis use #module::{#form, #message};
+    })
+}
+
+/// `enabled_by` must refer to a `bool` field before it.
+fn check_enabled_by(fields: &[Field]) -> syn::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        let Some(by) = &field.enabled_by else {
+            continue;
+        };
+        match fields[..i].iter().find(|f| f.ident == *by) {
+            Some(Field {
+                kind: Kind::Check, ..
+            }) => {}
+            Some(_) => {
+                return Err(syn::Error::new(
+                    by.span(),
+                    format!("`enabled_by` field `{by}` must be a `bool`"),
+                ));
+            }
+            None => {
+                return Err(syn::Error::new(
+                    by.span(),
+                    format!(
+                        "`enabled_by` field `{by}` must be a shown field declared before `{}`",
+                        field.ident
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn expand_options_enum(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[derive(OptionsEnum)] does not support generics",
+        ));
+    }
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "#[derive(OptionsEnum)] must be put on an enum with unit variants",
+        ));
+    };
+
+    let mut labels = Vec::new();
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.fields.span(),
+                "#[derive(OptionsEnum)] only supports unit variants",
+            ));
+        }
+        let mut label = None;
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident(ATTR))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("label") {
+                    label = Some(meta.value()?.parse::<Expr>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `label`"))
+                }
+            })?;
+        }
+        labels.push(match label {
+            Some(label) => label.into_token_stream(),
+            None => {
+                LitStr::new(&variant.ident.to_string(), variant.ident.span()).into_token_stream()
+            }
+        });
+        variants.push(&variant.ident);
+    }
+    let indices = 0..variants.len();
+    let indices2 = indices.clone();
+
+    Ok(quote! {
+        impl ::everything_plugin::ui::winio::form::OptionsEnum for #ident {
+            fn labels() -> ::std::vec::Vec<::std::string::String> {
+                ::std::vec![#(::std::string::ToString::to_string(&#labels)),*]
+            }
+
+            fn index(&self) -> usize {
+                match self {
+                    #(Self::#variants => #indices,)*
+                }
+            }
+
+            fn from_index(index: usize) -> ::std::option::Option<Self> {
+                match index {
+                    #(#indices2 => ::std::option::Option::Some(Self::#variants),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn form_err(input: DeriveInput) -> String {
+        expand_options_form(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn form() {
+        let tokens = expand_options_form(parse_quote! {
+            #[options_form(app = App)]
+            struct Config {
+                enabled: bool,
+                #[options_form(enabled_by = enabled)]
+                s: String,
+                n: u16,
+                #[options_form(combo)]
+                mode: Mode,
+                #[options_form(skip)]
+                path: Option<PathBuf>,
+            }
+        })
+        .unwrap()
+        .to_string();
+        assert!(tokens.contains("pub struct ConfigForm"), "{tokens}");
+        assert!(tokens.contains("with_config"), "{tokens}");
+        assert!(!tokens.contains("field_path"), "{tokens}");
+        // The winio prelude also has `App`
+        assert!(!tokens.contains("use super :: *"), "{tokens}");
+    }
+
+    #[test]
+    fn missing_app() {
+        let e = form_err(parse_quote! {
+            struct Config {
+                enabled: bool,
+            }
+        });
+        assert_eq!(e, "missing `#[options_form(app = App)]`");
+    }
+
+    #[test]
+    fn unsupported_type() {
+        for ty in [
+            quote! { Option<String> },
+            quote! { Vec<u32> },
+            quote! { PathBuf },
+            quote! { Mode },
+        ] {
+            let e = form_err(parse_quote! {
+                #[options_form(app = App)]
+                struct Config {
+                    field: #ty,
+                }
+            });
+            assert!(
+                e.starts_with("unsupported type of field `field`") && e.contains("skip"),
+                "{ty}: {e}"
+            );
+        }
+    }
+
+    #[test]
+    fn bad_enabled_by() {
+        let e = form_err(parse_quote! {
+            #[options_form(app = App)]
+            struct Config {
+                n: u32,
+                #[options_form(enabled_by = n)]
+                s: String,
+            }
+        });
+        assert_eq!(e, "`enabled_by` field `n` must be a `bool`");
+
+        let e = form_err(parse_quote! {
+            #[options_form(app = App)]
+            struct Config {
+                #[options_form(enabled_by = enabled)]
+                s: String,
+                enabled: bool,
+            }
+        });
+        assert_eq!(
+            e,
+            "`enabled_by` field `enabled` must be a shown field declared before `s`"
+        );
+
+        let e = form_err(parse_quote! {
+            #[options_form(app = App)]
+            struct Config {
+                #[options_form(skip)]
+                enabled: bool,
+                #[options_form(enabled_by = enabled)]
+                s: String,
+            }
+        });
+        assert_eq!(
+            e,
+            "`enabled_by` field `enabled` must be a shown field declared before `s`"
+        );
+    }
+
+    #[test]
+    fn unknown_attribute() {
+        let e = form_err(parse_quote! {
+            #[options_form(app = App)]
+            struct Config {
+                #[options_form(hidden)]
+                s: String,
+            }
+        });
+        assert_eq!(
+            e,
+            "expected `label`, `help`, `group`, `enabled_by`, `combo` or `skip`"
+        );
+    }
+
+    #[test]
+    fn non_unit_variant() {
+        for variant in [quote! { B(u32) }, quote! { B { n: u32 } }] {
+            let e = expand_options_enum(parse_quote! {
+                enum Mode {
+                    A,
+                    #variant,
+                }
+            })
+            .unwrap_err()
+            .to_string();
+            assert_eq!(e, "#[derive(OptionsEnum)] only supports unit variants");
+        }
+    }
+}
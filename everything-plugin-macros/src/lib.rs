@@ -0,0 +1,276 @@
+//! Procedural macros for [everything-plugin](https://docs.rs/everything-plugin).
+//!
+//! Use them through `everything_plugin` with the `macros` feature instead of depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{ToTokens, quote};
+use syn::{
+    DeriveInput, Expr, ExprLit, Ident, ItemImpl, Lit, MetaNameValue, Token, Type,
+    parse_macro_input, punctuated::Punctuated, spanned::Spanned,
+};
+
+mod form;
+
+/// Declare an Everything plugin on the `impl PluginApp for App` block, instead of [`plugin_main!`](https://docs.rs/everything-plugin/latest/everything_plugin/macro.plugin_main.html).
+///
+/// Generates:
+/// - The `HANDLER` static and the `everything_plugin_proc` export, as `plugin_main!`
+/// - `App::handler()` to access the `HANDLER`, so user code doesn't need to import it from `crate`
+///
+/// Arguments (all optional):
+/// - `name`: Defaults to `CARGO_PKG_NAME`
+/// - `description`: Defaults to `CARGO_PKG_DESCRIPTION`
+/// - `author`: Defaults to `CARGO_PKG_AUTHORS`
+/// - `version`: Defaults to `CARGO_PKG_VERSION`
+/// - `link`: Defaults to `CARGO_PKG_HOMEPAGE`, or `CARGO_PKG_REPOSITORY`
+/// - `options_pages`: An array of `OptionsPage`s
+/// - Any other `PluginHandlerBuilder` setter, e.g. `config_store = ConfigFile::default()`
+///
+/// Metadata fields are omitted if the Cargo package field is empty.
+///
+/// ## Example
+/// ```ignore
+/// #[everything_plugin(
+///     name = "Test Plugin",
+///     options_pages = [
+///         OptionsPage::builder()
+///             .name("Test Plugin")
+///             .load(ui::winio::spawn::<options::MainModel>)
+///             .build(),
+///     ],
+/// )]
+/// impl PluginApp for App {
+///     // ...
+/// }
+///
+/// App::handler().with_app(|app| app.config().s.clone());
+/// ```
+#[proc_macro_attribute]
+pub fn everything_plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args =
+        parse_macro_input!(attr with Punctuated::<MetaNameValue, Token![,]>::parse_terminated);
+    let item = parse_macro_input!(item as ItemImpl);
+    let fallback = item.to_token_stream();
+    match expand(args, item) {
+        Ok(tokens) => tokens.into(),
+        // Keep the impl to avoid cascading errors
+        Err(e) => {
+            let e = e.to_compile_error();
+            quote! { #e #fallback }.into()
+        }
+    }
+}
+
+/// Generate an options page component from the config struct.
+///
+/// See [`everything_plugin::ui::winio::form`](https://docs.rs/everything-plugin/latest/everything_plugin/ui/winio/form/index.html).
+#[proc_macro_derive(OptionsForm, attributes(options_form))]
+pub fn derive_options_form(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    form::expand_options_form(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `OptionsEnum` for a fieldless enum, to be shown as a `ComboBox` by `#[derive(OptionsForm)]` with `#[options_form(combo)]`.
+///
+/// Variants can be labeled with `#[options_form(label = expr)]`, otherwise the variant name is used.
+#[proc_macro_derive(OptionsEnum, attributes(options_form))]
+pub fn derive_options_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    form::expand_options_enum(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// `(key, CARGO_PKG_* variables in order of preference)`
+const METADATA: [(&str, &[&str]); 5] = [
+    ("name", &["CARGO_PKG_NAME"]),
+    ("description", &["CARGO_PKG_DESCRIPTION"]),
+    ("author", &["CARGO_PKG_AUTHORS"]),
+    ("version", &["CARGO_PKG_VERSION"]),
+    ("link", &["CARGO_PKG_HOMEPAGE", "CARGO_PKG_REPOSITORY"]),
+];
+
+fn expand(
+    args: Punctuated<MetaNameValue, Token![,]>,
+    item: ItemImpl,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let self_ty = check_impl(&item)?;
+
+    let mut keys: Vec<Ident> = Vec::new();
+    let mut setters = Vec::new();
+    let mut metadata: Vec<(&str, Option<Expr>)> =
+        METADATA.iter().map(|(key, _)| (*key, None)).collect();
+    for arg in args {
+        let key = arg.path.require_ident()?.clone();
+        if let Some(prev) = keys.iter().find(|prev| **prev == key) {
+            let mut e = syn::Error::new(key.span(), format!("duplicate argument `{key}`"));
+            e.combine(syn::Error::new(prev.span(), "first defined here"));
+            return Err(e);
+        }
+        keys.push(key.clone());
+
+        let value = arg.value;
+        if let Some((_, slot)) = metadata.iter_mut().find(|(name, _)| key == name) {
+            check_c_string(&value)?;
+            *slot = Some(value);
+        } else if key == "options_pages" {
+            let Expr::Array(pages) = value else {
+                return Err(syn::Error::new(
+                    value.span(),
+                    "expected an array of `OptionsPage`s, e.g. `[OptionsPage::builder().name(\"...\").load(...).build()]`",
+                ));
+            };
+            let pages = pages.elems;
+            setters.push(quote! { .options_pages(::std::vec![#pages]) });
+        } else {
+            // Unknown setters are reported by the compiler on `key`
+            setters.push(quote! { .#key(#value) });
+        }
+    }
+
+    let mut metadata_setters = Vec::new();
+    for ((key, vars), (_, value)) in METADATA.iter().zip(metadata) {
+        let setter = Ident::new(key, Span::call_site());
+        let value = match value {
+            Some(value) => value.into_token_stream(),
+            None => {
+                // Cargo sets `CARGO_PKG_*` for rustc, which runs this macro
+                let Some(var) = vars
+                    .iter()
+                    .find(|var| std::env::var(var).is_ok_and(|v| !v.is_empty()))
+                else {
+                    if *key == "name" {
+                        return Err(syn::Error::new(
+                            Span::call_site(),
+                            "`name` is required when not built by Cargo",
+                        ));
+                    }
+                    continue;
+                };
+                match *key {
+                    // Colon-separated
+                    "author" => quote! { ::std::env!(#var).replace(':', ", ") },
+                    _ => quote! { ::std::env!(#var) },
+                }
+            }
+        };
+        metadata_setters.push(quote! { .#setter(#value) });
+    }
+
+    Ok(quote! {
+        #item
+
+        impl #self_ty {
+            /// The plugin handler generated by `#[everything_plugin]`.
+            #[allow(dead_code)]
+            pub fn handler() -> &'static ::everything_plugin::PluginHandler<Self> {
+                &HANDLER
+            }
+        }
+
+        ::everything_plugin::plugin_main!(#self_ty, {
+            ::everything_plugin::PluginHandler::builder()
+                #(#metadata_setters)*
+                #(#setters)*
+                .build()
+        });
+    })
+}
+
+/// Must be `impl PluginApp for Type`, without generics.
+fn check_impl(item: &ItemImpl) -> syn::Result<&Type> {
+    let Some((None, trait_path, _)) = &item.trait_ else {
+        return Err(syn::Error::new(
+            item.impl_token.span,
+            "#[everything_plugin] must be put on `impl PluginApp for App`",
+        ));
+    };
+    if trait_path
+        .segments
+        .last()
+        .is_none_or(|segment| segment.ident != "PluginApp")
+    {
+        return Err(syn::Error::new(
+            trait_path.span(),
+            "#[everything_plugin] must be put on `impl PluginApp for App`",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item.generics.span(),
+            "the plugin app cannot be generic, as it is stored in a static",
+        ));
+    }
+    Ok(&item.self_ty)
+}
+
+/// Metadata strings are converted to `CString`s, which cannot contain NUL.
+fn check_c_string(value: &Expr) -> syn::Result<()> {
+    if let Expr::Lit(ExprLit {
+        lit: Lit::Str(s), ..
+    }) = value
+        && s.value().contains('\0')
+    {
+        return Err(syn::Error::new(s.span(), "cannot contain NUL characters"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn expand_err(args: Punctuated<MetaNameValue, Token![,]>, item: ItemImpl) -> String {
+        expand(args, item).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn duplicate_argument() {
+        let e = expand_err(
+            parse_quote! { name = "A", name = "B" },
+            parse_quote! { impl PluginApp for App {} },
+        );
+        assert_eq!(e, "duplicate argument `name`");
+    }
+
+    #[test]
+    fn not_plugin_app() {
+        let message = "#[everything_plugin] must be put on `impl PluginApp for App`";
+        let args: Punctuated<MetaNameValue, Token![,]> = parse_quote! { name = "A" };
+        assert_eq!(
+            expand_err(args.clone(), parse_quote! { impl App {} }),
+            message
+        );
+        assert_eq!(
+            expand_err(args.clone(), parse_quote! { impl Default for App {} }),
+            message
+        );
+        assert_eq!(
+            expand_err(args, parse_quote! { impl<T> PluginApp for App<T> {} }),
+            "the plugin app cannot be generic, as it is stored in a static"
+        );
+    }
+
+    #[test]
+    fn nul_metadata() {
+        let e = expand_err(
+            parse_quote! { name = "A\0" },
+            parse_quote! { impl PluginApp for App {} },
+        );
+        assert_eq!(e, "cannot contain NUL characters");
+    }
+
+    #[test]
+    fn options_pages_not_array() {
+        let e = expand_err(
+            parse_quote! { name = "A", options_pages = pages() },
+            parse_quote! { impl PluginApp for App {} },
+        );
+        assert!(e.starts_with("expected an array of `OptionsPage`s"), "{e}");
+    }
+}
@@ -9,3 +9,6 @@ bindgen ../target/sdk/everything_plugin.h -o src/sys.rs `
     --merge-extern-blocks `
     --rustified-enum .* `
     --verbose
+
+# Layout asserts only hold on Windows, e.g. `c_ulong` is 64-bit on Linux
+(Get-Content src/sys.rs) -replace '^const _: \(\) = \{$', "#[cfg(windows)]`nconst _: () = {" | Set-Content src/sys.rs
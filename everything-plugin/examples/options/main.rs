@@ -1,23 +1,35 @@
 use everything_plugin::{
-    PluginApp, PluginHandler, plugin_main,
-    ui::{self, OptionsPage},
+    PluginApp,
+    data::FieldError,
+    everything_plugin,
+    ui::{
+        self, OptionsPage,
+        winio::form::{OptionsEnum, OptionsForm},
+    },
 };
 use serde::{Deserialize, Serialize};
 
 mod options;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(OptionsEnum, Serialize, Deserialize, Debug, Default)]
 pub enum Mode {
     #[default]
     A,
+    #[options_form(label = "Mode B")]
     B,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The "Generated" page is generated by `#[derive(OptionsForm)]`.
+#[derive(OptionsForm, Serialize, Deserialize, Debug)]
+#[options_form(app = App)]
 pub struct Config {
+    #[options_form(label = "Enable")]
     enabled: bool,
+    #[options_form(label = "Switch", enabled_by = enabled)]
     b: bool,
+    #[options_form(label = "Mode", group = "Advanced", enabled_by = enabled, combo)]
     e: Mode,
+    #[options_form(label = "Message", help = "Must not be empty", enabled_by = enabled)]
     s: String,
 }
 
@@ -36,6 +48,23 @@ pub struct App {
     config: Config,
 }
 
+#[everything_plugin(
+    name = "Test Plugin",
+    description = "A test plugin for Everything",
+    author = "Chaoses-Ib",
+    version = env!("CARGO_PKG_VERSION"),
+    link = "https://github.com/Chaoses-Ib/IbEverythingLib",
+    options_pages = [
+        OptionsPage::builder()
+            .name("Test Plugin")
+            .load(ui::winio::spawn::<options::MainModel>)
+            .build(),
+        OptionsPage::builder()
+            .name("Generated")
+            .load(ui::winio::spawn::<ConfigForm>)
+            .build(),
+    ],
+)]
 impl PluginApp for App {
     type Config = Config;
 
@@ -45,6 +74,13 @@ impl PluginApp for App {
         }
     }
 
+    fn validate(config: &Self::Config) -> Result<(), Vec<FieldError>> {
+        if config.s.is_empty() {
+            return Err(vec![FieldError::new("s", "Message cannot be empty")]);
+        }
+        Ok(())
+    }
+
     fn config(&self) -> &Self::Config {
         &self.config
     }
@@ -53,19 +89,3 @@ impl PluginApp for App {
         self.config
     }
 }
-
-plugin_main!(App, {
-    PluginHandler::builder()
-        .name("Test Plugin")
-        .description("A test plugin for Everything")
-        .author("Chaoses-Ib")
-        .version("0.1.0")
-        .link("https://github.com/Chaoses-Ib/IbEverythingLib")
-        .options_pages(vec![
-            OptionsPage::builder()
-                .name("Test Plugin")
-                .load(ui::winio::spawn::<options::MainModel>)
-                .build(),
-        ])
-        .build()
-});
@@ -1,6 +1,6 @@
-use everything_plugin::ui::winio::prelude::*;
+use everything_plugin::ui::winio::{prelude::*, toolbar::ConfigToolbar};
 
-use crate::{App, HANDLER, Mode};
+use crate::{App, Config, Mode};
 
 pub struct MainModel {
     window: Child<Window>,
@@ -10,6 +10,7 @@ pub struct MainModel {
     s: Child<Edit>,
     e_label: Child<Label>,
     e: Child<ComboBox>,
+    toolbar: ConfigToolbar,
 }
 
 #[derive(Debug)]
@@ -18,6 +19,9 @@ pub enum MainMessage {
     Close,
     Redraw,
     EnabledClick,
+    Export,
+    Import,
+    Reset,
     OptionsPage(OptionsPageMessage<App>),
 }
 
@@ -27,13 +31,38 @@ impl From<OptionsPageMessage<App>> for MainMessage {
     }
 }
 
+impl MainModel {
+    fn set_config(&mut self, config: &Config) {
+        self.enabled.set_checked(config.enabled);
+        self.b.set_checked(config.b);
+
+        self.e.set_selection(Some(match config.e {
+            Mode::A => 0,
+            Mode::B => 1,
+        }));
+
+        self.s.set_text(&config.s);
+    }
+
+    fn write_config(&self, config: &mut Config) {
+        config.enabled = self.enabled.is_checked();
+        config.b = self.b.is_checked();
+        config.e = match self.e.selection() {
+            Some(0) => Mode::A,
+            Some(1) => Mode::B,
+            _ => Default::default(),
+        };
+        config.s = self.s.text();
+    }
+}
+
 impl Component for MainModel {
     type Event = ();
     type Init<'a> = OptionsPageInit<'a, App>;
     type Message = MainMessage;
 
     fn init(mut init: Self::Init<'_>, sender: &ComponentSender<Self>) -> Self {
-        let mut window = init.window(sender);
+        let window = init.window(sender);
         // window.set_size(Size::new(800.0, 600.0));
 
         let mut enabled = Child::<CheckBox>::init(&window);
@@ -50,27 +79,11 @@ impl Component for MainModel {
 
         let mut s_label = Child::<Label>::init(&window);
         s_label.set_text("Message:");
-        let mut s = Child::<Edit>::init(&window);
-
-        HANDLER.with_app(|a| {
-            let config = a.config();
+        let s = Child::<Edit>::init(&window);
 
-            enabled.set_checked(config.enabled);
-            b.set_checked(config.b);
+        let toolbar = ConfigToolbar::new(&window);
 
-            e.set_selection(Some(match config.e {
-                Mode::A => 0,
-                Mode::B => 1,
-            }));
-
-            s.set_text(&config.s);
-        });
-
-        sender.post(MainMessage::EnabledClick);
-
-        window.show();
-
-        Self {
+        let mut model = Self {
             window,
             enabled,
             b,
@@ -78,7 +91,15 @@ impl Component for MainModel {
             s,
             e_label,
             e,
-        }
+            toolbar,
+        };
+        App::handler().with_app(|a| model.set_config(a.config()));
+
+        sender.post(MainMessage::EnabledClick);
+
+        model.window.show();
+
+        model
     }
 
     async fn start(&mut self, sender: &ComponentSender<Self>) -> ! {
@@ -90,6 +111,15 @@ impl Component for MainModel {
             },
             self.enabled => {
                 CheckBoxEvent::Click => MainMessage::EnabledClick
+            },
+            self.toolbar.export => {
+                ButtonEvent::Click => MainMessage::Export,
+            },
+            self.toolbar.import => {
+                ButtonEvent::Click => MainMessage::Import,
+            },
+            self.toolbar.reset => {
+                ButtonEvent::Click => MainMessage::Reset,
             }
         }
     }
@@ -110,20 +140,49 @@ impl Component for MainModel {
                 self.s.set_enabled(enabled);
                 false
             }
+            MainMessage::Export => {
+                // All fields are on this page
+                let mut config = Config::default();
+                self.write_config(&mut config);
+                self.toolbar
+                    .export(App::handler(), &self.window, &config)
+                    .await;
+                false
+            }
+            MainMessage::Import => {
+                if let Some(config) = self.toolbar.import(App::handler(), &self.window).await {
+                    self.set_config(&config);
+                    sender.post(MainMessage::EnabledClick);
+                }
+                false
+            }
+            MainMessage::Reset => {
+                if let Some(config) = self.toolbar.reset::<App>(&self.window).await {
+                    self.set_config(&config);
+                    sender.post(MainMessage::EnabledClick);
+                }
+                false
+            }
             MainMessage::OptionsPage(m) => {
                 tracing::debug!(?m, "Options page message");
                 match m {
                     OptionsPageMessage::Save(config, tx) => {
-                        config.enabled = self.enabled.is_checked();
-                        config.b = self.b.is_checked();
-                        config.e = match self.e.selection() {
-                            Some(0) => Mode::A,
-                            Some(1) => Mode::B,
-                            _ => Default::default(),
-                        };
-                        config.s = self.s.text();
+                        self.write_config(config);
                         tx.send(config).unwrap()
                     }
+                    OptionsPageMessage::Invalid(errors) => {
+                        let errors = errors
+                            .iter()
+                            .map(|e| e.to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        MessageBox::new()
+                            .title("Invalid options")
+                            .message(errors)
+                            .buttons(MessageBoxButton::Ok)
+                            .show(&self.window)
+                            .await;
+                    }
                 }
                 false
             }
@@ -146,11 +205,19 @@ impl Component for MainModel {
             self.s => { column: 1, row: 1, margin: m },
         };
 
+        let mut toolbar = layout! {
+            StackPanel::new(Orient::Horizontal),
+            self.toolbar.export => { margin: m_l },
+            self.toolbar.import => { margin: m_l },
+            self.toolbar.reset => { margin: m_l },
+        };
+
         let mut grid = layout! {
-            Grid::from_str("auto,1*", "auto,auto,auto,1*").unwrap(),
+            Grid::from_str("auto,1*", "auto,auto,auto,1*,auto").unwrap(),
             self.enabled => { column: 0, row: 0, margin: m },
             self.b => { column: 0, row: 1, margin: m },
             form => { column: 0, row: 2, margin: m },
+            toolbar => { column: 0, row: 4, margin: m },
         };
         grid.set_size(csize);
     }
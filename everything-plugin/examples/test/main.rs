@@ -35,6 +35,11 @@ impl PluginApp for App {
     }
 }
 
+/// Run the options pages without Everything, see `standalone.rs`.
+pub fn standalone() {
+    everything_plugin::standalone::run_standalone(&*HANDLER, "test.json");
+}
+
 plugin_main!(App, {
     PluginHandler::builder()
         .name("Test Plugin")
@@ -0,0 +1,8 @@
+//! The test plugin's options pages without Everything: `cargo run --example test-standalone`
+
+#[path = "main.rs"]
+mod plugin;
+
+fn main() {
+    plugin::standalone();
+}
@@ -4,17 +4,7 @@
 
 use everything_plugin::ui::winio::prelude::*;
 
-use crate::{App, HANDLER};
-
-#[allow(dead_code)]
-fn main() {
-    // #[cfg(feature = "enable_log")]
-    // tracing_subscriber::fmt()
-    //     .with_max_level(compio_log::Level::INFO)
-    //     .init();
-
-    winio::ui::App::new("rs.compio.winio.widgets").run::<MainModel>(());
-}
+use super::{App, HANDLER};
 
 pub struct MainModel {
     window: Child<Window>,
@@ -248,6 +238,7 @@ impl Component for MainModel {
                         config.s = self.mltext.text();
                         tx.send(config).unwrap()
                     }
+                    OptionsPageMessage::Invalid(_errors) => (),
                 }
                 false
             }
@@ -1,5 +1,6 @@
 use std::ffi::CString;
 
+#[cfg(windows)]
 use windows_sys::Win32::{
     Globalization::{GetLocaleInfoW, GetThreadUILanguage, LOCALE_SNAME},
     System::SystemServices::LOCALE_NAME_MAX_LENGTH,
@@ -62,29 +63,47 @@ impl PluginHost {
     }
 
     pub fn get_language_name(language: u16) -> String {
-        let mut lcdata = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
-        _ = unsafe {
-            GetLocaleInfoW(
-                language as u32,
-                LOCALE_SNAME,
-                lcdata.as_mut_ptr(),
-                size_of_val(&lcdata) as i32,
-            )
-        };
-        String::from_utf16_lossy(&lcdata)
-            .trim_end_matches('\0')
-            .to_string()
+        #[cfg(feature = "testing")]
+        if let Some(name) = crate::testing::language_name(Some(language)) {
+            return name;
+        }
+        #[cfg(windows)]
+        {
+            let mut lcdata = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+            _ = unsafe {
+                GetLocaleInfoW(
+                    language as u32,
+                    LOCALE_SNAME,
+                    lcdata.as_mut_ptr(),
+                    size_of_val(&lcdata) as i32,
+                )
+            };
+            String::from_utf16_lossy(&lcdata)
+                .trim_end_matches('\0')
+                .to_string()
+        }
+        #[cfg(not(windows))]
+        String::new()
     }
 
     pub fn get_thread_language_name() -> String {
-        let language = unsafe { GetThreadUILanguage() };
-        Self::get_language_name(language)
+        #[cfg(feature = "testing")]
+        if let Some(name) = crate::testing::language_name(None) {
+            return name;
+        }
+        #[cfg(windows)]
+        {
+            let language = unsafe { GetThreadUILanguage() };
+            Self::get_language_name(language)
+        }
+        #[cfg(not(windows))]
+        String::new()
     }
 
     pub fn config_get_language_name(&self) -> String {
-        let language = self
-            .config_get_language()
-            .unwrap_or_else(|| unsafe { GetThreadUILanguage() });
-        Self::get_language_name(language)
+        match self.config_get_language() {
+            Some(language) => Self::get_language_name(language),
+            None => Self::get_thread_language_name(),
+        }
     }
 }
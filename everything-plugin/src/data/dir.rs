@@ -0,0 +1,134 @@
+//! Named-instance aware data paths.
+//!
+//! [Named instances](https://www.voidtools.com/support/everything/multiple_instances/#named_instances) (e.g. `Everything-1.5a`) share the same app data directories, so files of different instances must be named differently, as Everything itself does with `Everything-1.5a.ini` and `Plugins-1.5a.ini`.
+//!
+//! [`DataDir`] applies the [`InstancePolicy`] selected with [`crate::PluginHandlerBuilder::instance_policy()`]:
+//! - [`InstancePolicy::PerInstance`] (default): `{name}-{instance_name}`
+//! - [`InstancePolicy::Shared`]: `{name}`, for data that is safe to share, e.g. caches of the file system
+//!
+//! Paths:
+//! - Config: `{app_data}\Plugins\{name}{-instance_name}\`
+//! - Cache: `{local_app_data}\Plugins\{name}{-instance_name}\Cache\`
+//! - Log: `{local_app_data}\Logs\`
+//!
+//! ## Example
+//! ```ignore
+//! let dir = HANDLER.data_dir().unwrap();
+//! let db = dir.cache_dir().join("index.db");
+//! ```
+
+use std::path::PathBuf;
+
+use crate::{PluginApp, PluginHandler, PluginHost};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InstancePolicy {
+    /// Each named instance has its own data.
+    #[default]
+    PerInstance,
+    /// All instances share the same data.
+    Shared,
+}
+
+#[derive(Clone, Copy)]
+pub struct DataDir<'a> {
+    host: &'a PluginHost,
+    name: &'a str,
+    /// `None` if not a named instance or [`InstancePolicy::Shared`].
+    instance_name: Option<&'a str>,
+}
+
+impl<'a> DataDir<'a> {
+    pub fn new(
+        host: &'a PluginHost,
+        name: &'a str,
+        instance_name: Option<&'a str>,
+        policy: InstancePolicy,
+    ) -> Self {
+        Self {
+            host,
+            name,
+            instance_name: match policy {
+                InstancePolicy::PerInstance => instance_name,
+                InstancePolicy::Shared => None,
+            },
+        }
+    }
+
+    pub fn host(&self) -> &'a PluginHost {
+        self.host
+    }
+
+    /// The plugin name, see [`PluginHandler::plugin_name()`].
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The instance name after applying [`InstancePolicy`].
+    pub fn instance_name(&self) -> Option<&'a str> {
+        self.instance_name
+    }
+
+    /// `{stem}-{instance_name}`, or `{stem}` if not a named instance or shared.
+    pub fn instance_stem(&self, stem: &str) -> String {
+        match self.instance_name {
+            Some(instance_name) => format!("{stem}-{instance_name}"),
+            None => stem.to_string(),
+        }
+    }
+
+    /// `{stem}{-instance_name}.{extension}`
+    pub fn instance_file_name(&self, stem: &str, extension: &str) -> String {
+        format!("{}.{extension}", self.instance_stem(stem))
+    }
+
+    /// `{app_data}\Plugins\{name}{-instance_name}\`, for config and other roaming data.
+    pub fn config_dir(&self) -> PathBuf {
+        self.host
+            .os_get_app_data_path()
+            .join("Plugins")
+            .join(self.instance_stem(self.name))
+    }
+
+    /// `{local_app_data}\Plugins\{name}{-instance_name}\`, for local data.
+    pub fn local_dir(&self) -> PathBuf {
+        self.host
+            .os_get_local_app_data_path()
+            .join("Plugins")
+            .join(self.instance_stem(self.name))
+    }
+
+    /// `{local_app_data}\Plugins\{name}{-instance_name}\Cache\`
+    pub fn cache_dir(&self) -> PathBuf {
+        self.local_dir().join("Cache")
+    }
+
+    /// `{local_app_data}\Logs\`. Files in it should be named with [`Self::instance_file_name()`].
+    pub fn log_dir(&self) -> PathBuf {
+        self.host.os_get_local_app_data_path().join("Logs")
+    }
+
+    /// `{app_data}\Backups\`. Files in it should be named with [`Self::instance_file_name()`].
+    pub fn backup_dir(&self) -> PathBuf {
+        self.host.os_get_app_data_path().join("Backups")
+    }
+
+    /// The non-official `plugins{-instance_name}.json`.
+    pub fn plugins_json_path(&self) -> PathBuf {
+        self.host
+            .os_get_app_data_path()
+            .join(self.instance_file_name("plugins", "json"))
+    }
+}
+
+impl<A: PluginApp> PluginHandler<A> {
+    /// `None` before handling `EVERYTHING_PLUGIN_PM_INIT`
+    pub fn data_dir(&self) -> Option<DataDir<'_>> {
+        Some(DataDir::new(
+            self.get_host()?,
+            self.plugin_name(),
+            self.instance_name(),
+            self.instance_policy,
+        ))
+    }
+}
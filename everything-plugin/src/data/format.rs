@@ -0,0 +1,188 @@
+//! How config is serialized, selected with [`crate::PluginHandlerBuilder::config_format()`].
+//!
+//! - [`Json`] (default): single-line unless pretty, so it can be stored in [`super::store::IniKey`]
+//! - [`Toml`] (`toml` feature): multi-line, for hand-edited configs in [`super::store::ConfigFile`]
+//! - [`Ron`] (`ron` feature): single-line unless pretty
+//!
+//! Formats convert between text and [`serde_json::Value`], which is what [`super::migrate`] operates on.
+//!
+//! ## Example
+//! ```ignore
+//! PluginHandler::builder()
+//!     .name("Test Plugin")
+//!     .config_store(ConfigFile::default())
+//!     .config_format(Toml)
+//!     .build()
+//! ```
+
+use std::error::Error;
+
+use serde_json::Value;
+
+pub type FormatError = Box<dyn Error + Send + Sync + 'static>;
+
+pub trait ConfigFormat: Send + Sync + 'static {
+    /// The file extension without `.`, e.g. `json`.
+    fn extension(&self) -> &'static str;
+
+    fn to_string(&self, config: &Value) -> Result<String, FormatError>;
+
+    fn parse(&self, s: &str) -> Result<Value, FormatError>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json {
+    /// Multi-line, cannot be used with [`super::store::IniKey`].
+    pub pretty: bool,
+}
+
+impl ConfigFormat for Json {
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn to_string(&self, config: &Value) -> Result<String, FormatError> {
+        Ok(match self.pretty {
+            true => serde_json::to_string_pretty(config)?,
+            false => serde_json::to_string(config)?,
+        })
+    }
+
+    fn parse(&self, s: &str) -> Result<Value, FormatError> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+/// Multi-line, cannot be used with [`super::store::IniKey`].
+///
+/// `null`s (i.e. `None`s) are omitted, as TOML has no null.
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Toml;
+
+#[cfg(feature = "toml")]
+impl ConfigFormat for Toml {
+    fn extension(&self) -> &'static str {
+        "toml"
+    }
+
+    fn to_string(&self, config: &Value) -> Result<String, FormatError> {
+        let mut config = config.clone();
+        remove_nulls(&mut config);
+        Ok(toml::to_string_pretty(&config)?)
+    }
+
+    fn parse(&self, s: &str) -> Result<Value, FormatError> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(feature = "toml")]
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(array) => array.iter_mut().for_each(remove_nulls),
+        _ => (),
+    }
+}
+
+#[cfg(feature = "ron")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ron {
+    /// Multi-line, cannot be used with [`super::store::IniKey`].
+    pub pretty: bool,
+}
+
+#[cfg(feature = "ron")]
+impl ConfigFormat for Ron {
+    fn extension(&self) -> &'static str {
+        "ron"
+    }
+
+    fn to_string(&self, config: &Value) -> Result<String, FormatError> {
+        Ok(match self.pretty {
+            true => ron::ser::to_string_pretty(config, Default::default())?,
+            false => ron::to_string(config)?,
+        })
+    }
+
+    fn parse(&self, s: &str) -> Result<Value, FormatError> {
+        Ok(ron::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        s: String,
+        n: u32,
+        f: f64,
+        b: bool,
+        none: Option<String>,
+        some: Option<i64>,
+        list: Vec<String>,
+        map: BTreeMap<String, u8>,
+        nested: Nested,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Nested {
+        s: String,
+    }
+
+    fn config() -> Config {
+        Config {
+            s: "a \"quoted\" = string".into(),
+            n: 42,
+            f: 1.5,
+            b: true,
+            none: None,
+            some: Some(-1),
+            list: vec!["x".into(), "y".into()],
+            map: [("k".into(), 1)].into(),
+            nested: Nested { s: "nested".into() },
+        }
+    }
+
+    fn round_trip(format: impl ConfigFormat) {
+        let value = serde_json::to_value(config()).unwrap();
+        let s = format.to_string(&value).unwrap();
+        let parsed: Config = serde_json::from_value(format.parse(&s).unwrap()).unwrap();
+        assert_eq!(parsed, config(), "{s}");
+    }
+
+    #[test]
+    fn json() {
+        round_trip(Json::default());
+        round_trip(Json { pretty: true });
+        assert!(
+            !Json::default()
+                .to_string(&Value::Null)
+                .unwrap()
+                .contains('\n')
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() {
+        round_trip(Toml);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn ron() {
+        round_trip(Ron::default());
+        round_trip(Ron { pretty: true });
+    }
+}
@@ -0,0 +1,1263 @@
+//! Store each config field in its own ini key, instead of a single JSON value under `_`.
+//!
+//! ```ini
+//! [MyPlugin.dll]
+//! enabled=true
+//! s=Hello
+//! window.width=800
+//! _keys=enabled,s,window.width
+//! ```
+//! - Structs and maps are flattened into dotted keys by a serde serializer ([`to_pairs()`]), and read back by a deserializer driven by the config type ([`from_pairs()`]).
+//!   `.`, `,`, `%`, characters special to ini lines (`=`, `;`, `[`, `]`), control characters and leading or trailing spaces in map keys are percent-encoded, e.g. `a=b` as `a%3Db`.
+//! - Strings are written as is, or JSON-quoted if multi-line, padded, quoted or `null`. Sequences and enum variants with data are written as JSON.
+//! - `None`s are omitted.
+//! - `_keys` lists the written keys, since ini keys cannot be enumerated.
+//! - Unknown keys (e.g. from a newer plugin version) are preserved, unless the config is migrated from an older version.
+//! - `_version` is written if [`super::migrate`] is used.
+//! - Fields that fail to deserialize fall back to their defaults one by one, instead of resetting the whole config.
+//!
+//! Must be used with [`super::format::Json`].
+//!
+//! ## Example
+//! ```ignore
+//! PluginHandler::builder()
+//!     .name("Test Plugin")
+//!     .config_store(IniKeys::<Config>::new())
+//!     .build()
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, btree_map},
+    fmt::{self, Display},
+    io,
+    marker::PhantomData,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use serde::{
+    Serialize,
+    de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor},
+    ser,
+};
+use serde_json::{Map, Value};
+use tracing::{debug, warn};
+
+use crate::data::{
+    migrate::Migrations,
+    store::{ConfigStore, StoreContext},
+};
+
+const KEYS: &str = "_keys";
+const VERSION: &str = "_version";
+
+pub struct IniKeys<C> {
+    /// The version and keys read but not in the config, written back on save.
+    unknown: Mutex<(u32, BTreeMap<String, String>)>,
+    /// The keys last read or written, cleared on remove.
+    keys: Mutex<Vec<String>>,
+    _config: PhantomData<fn() -> C>,
+}
+
+impl<C: Serialize + DeserializeOwned + Default> IniKeys<C> {
+    pub fn new() -> Self {
+        Self {
+            unknown: Default::default(),
+            keys: Default::default(),
+            _config: PhantomData,
+        }
+    }
+}
+
+impl<C: Serialize + DeserializeOwned + Default> Default for IniKeys<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl<C: Serialize + DeserializeOwned + Default + 'static> ConfigStore for IniKeys<C> {
+    fn load(&self, cx: &StoreContext) -> io::Result<Option<String>> {
+        let Some(keys) = cx.get_setting(KEYS).filter(|keys| !keys.is_empty()) else {
+            return Ok(None);
+        };
+        *self.keys.lock().unwrap() = keys.split(',').map(String::from).collect();
+        let mut pairs = BTreeMap::new();
+        for key in keys.split(',') {
+            if let Some(raw) = cx.get_setting(key)
+                && !key.is_empty()
+            {
+                pairs.insert(key.to_string(), raw);
+            }
+        }
+        let version = cx.get_setting(VERSION).and_then(|v| v.parse::<u32>().ok());
+
+        // Typed by `C` in `repair()` after migrations
+        let config = deserialize_tracked::<Value>(&pairs)
+            .0
+            .map_err(invalid_data)?;
+
+        let (_, used) = deserialize_tracked::<C>(&pairs);
+        let unknown: BTreeMap<String, String> = pairs
+            .into_iter()
+            .filter(|(key, _)| !used.contains(key))
+            .collect();
+        debug!(?unknown, "Plugin config unknown keys");
+        *self.unknown.lock().unwrap() = (version.unwrap_or_default(), unknown);
+
+        let config = match version {
+            Some(version) => Migrations::envelope(version, config),
+            None => config,
+        };
+        Ok(Some(config.to_string()))
+    }
+
+    fn save(&self, cx: &StoreContext, config: &str) -> io::Result<()> {
+        let value: Value = serde_json::from_str(config)
+            .map_err(|_| invalid_data("IniKeys requires data::format::Json"))?;
+        let (version, config) = Migrations::unwrap_envelope(value);
+
+        let mut pairs = to_pairs(&config).map_err(invalid_data)?;
+        let (read_version, unknown) = &*self.unknown.lock().unwrap();
+        // Keys of older versions are renamed or removed by migrations
+        if *read_version >= version {
+            for (key, raw) in unknown {
+                pairs.entry(key.clone()).or_insert_with(|| raw.clone());
+            }
+        }
+
+        for (key, value) in &pairs {
+            cx.set_setting(key, value)?;
+        }
+        // Keys are percent-encoded, so `,` only appears as a separator
+        let keys = pairs
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+        cx.set_setting(KEYS, &keys)?;
+        *self.keys.lock().unwrap() = pairs.into_keys().collect();
+        if version != 0 {
+            cx.set_setting(VERSION, &version.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, cx: &StoreContext) -> io::Result<()> {
+        if !cx.can_set_setting() {
+            return Ok(());
+        }
+        for key in self.keys.lock().unwrap().iter() {
+            cx.set_setting(key, "")?;
+        }
+        cx.set_setting(VERSION, "")?;
+        // An empty `_keys` is read as not stored
+        cx.set_setting(KEYS, "")
+    }
+
+    fn repair(&self, config: Value) -> Value {
+        if serde_json::from_value::<C>(config.clone()).is_ok() {
+            return config;
+        }
+        // Values are guessed from the text without the type, e.g. `s=1` is a number
+        match to_pairs(&config) {
+            Ok(pairs) => serde_json::to_value(from_pairs::<C>(&pairs)).unwrap_or(config),
+            Err(e) => {
+                warn!(%e, "Plugin config repair error");
+                config
+            }
+        }
+    }
+}
+
+/// An error of [`to_pairs()`] or [`try_from_pairs()`].
+#[derive(Debug)]
+pub struct IniError(String);
+
+impl Display for IniError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for IniError {}
+
+impl ser::Error for IniError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for IniError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// Serialize a config into flattened `key=value` pairs, without [`IniKeys`] bookkeeping keys.
+///
+/// The config must be a struct or map.
+pub fn to_pairs<T: Serialize + ?Sized>(config: &T) -> Result<BTreeMap<String, String>, IniError> {
+    let mut pairs = BTreeMap::new();
+    config.serialize(PairsSerializer {
+        out: &mut pairs,
+        path: String::new(),
+    })?;
+    Ok(pairs)
+}
+
+/// Deserialize a config from flattened `key=value` pairs, falling back to the default of each bad field.
+pub fn from_pairs<T: Serialize + DeserializeOwned + Default>(
+    pairs: &BTreeMap<String, String>,
+) -> T {
+    if let Ok(config) = try_from_pairs(pairs) {
+        return config;
+    }
+
+    let mut good = to_pairs(&T::default()).unwrap_or_default();
+    for (key, raw) in pairs {
+        let mut candidate = good.clone();
+        candidate.insert(key.clone(), raw.clone());
+        match try_from_pairs::<T>(&candidate) {
+            Ok(_) => good = candidate,
+            Err(e) => warn!(key, %e, "Plugin config field reset to default"),
+        }
+    }
+    try_from_pairs(&good).unwrap_or_default()
+}
+
+/// Deserialize a config from flattened `key=value` pairs.
+pub fn try_from_pairs<T: DeserializeOwned>(
+    pairs: &BTreeMap<String, String>,
+) -> Result<T, IniError> {
+    deserialize_tracked(pairs).0
+}
+
+/// Also returns the keys used by `T`.
+fn deserialize_tracked<T: DeserializeOwned>(
+    pairs: &BTreeMap<String, String>,
+) -> (Result<T, IniError>, BTreeSet<String>) {
+    let root = Node::from_pairs(pairs);
+    let used = RefCell::default();
+    let result = T::deserialize(PairsDeserializer {
+        node: &root,
+        path: String::new(),
+        used: &used,
+    });
+    (result, used.into_inner())
+}
+
+/// Separators, and characters an ini line parser would misread, e.g. Everything splits a line at the first `=`.
+const ESCAPED: [char; 7] = ['%', '.', ',', '=', ';', '[', ']'];
+
+fn escape(segment: &str) -> String {
+    let last = segment.chars().count().saturating_sub(1);
+    let mut escaped = String::with_capacity(segment.len());
+    for (i, c) in segment.chars().enumerate() {
+        if ESCAPED.contains(&c) || c.is_control() || (c == ' ' && (i == 0 || i == last)) {
+            for b in c.encode_utf8(&mut [0; 4]).bytes() {
+                escaped.push_str(&format!("%{b:02X}"));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+fn unescape(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                unescaped.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                unescaped.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// `prefix.segment`, with `segment` escaped.
+fn join(prefix: &str, segment: &str) -> String {
+    match prefix {
+        "" => escape(segment),
+        prefix => format!("{prefix}.{}", escape(segment)),
+    }
+}
+
+fn format_str(s: &str) -> String {
+    if s.contains(['\n', '\r']) || s.starts_with('"') || s.trim() != s || s == "null" {
+        serde_json::to_string(s).unwrap()
+    } else {
+        s.to_string()
+    }
+}
+
+fn parse_str(raw: &str) -> String {
+    match raw.starts_with('"') {
+        true => serde_json::from_str(raw).unwrap_or_else(|_| raw.to_string()),
+        false => raw.to_string(),
+    }
+}
+
+/// Without the type, e.g. for [`Value`].
+fn guess(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, IniError> {
+    serde_json::to_value(value).map_err(ser::Error::custom)
+}
+
+struct PairsSerializer<'a> {
+    out: &'a mut BTreeMap<String, String>,
+    path: String,
+}
+
+impl PairsSerializer<'_> {
+    fn leaf(self, raw: String) -> Result<(), IniError> {
+        if self.path.is_empty() {
+            return Err(IniError("config must be a struct or map".into()));
+        }
+        self.out.insert(self.path, raw);
+        Ok(())
+    }
+
+    fn json(self, variant: Option<&str>, value: Value) -> Result<(), IniError> {
+        let value = match variant {
+            Some(variant) => Value::Object(Map::from_iter([(variant.to_string(), value)])),
+            None => value,
+        };
+        self.leaf(value.to_string())
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $ty:ty,)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), IniError> {
+                self.leaf(v.to_string())
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = IniError;
+    type SerializeSeq = JsonSeq<'a>;
+    type SerializeTuple = JsonSeq<'a>;
+    type SerializeTupleStruct = JsonSeq<'a>;
+    type SerializeTupleVariant = JsonSeq<'a>;
+    type SerializeMap = Flatten<'a>;
+    type SerializeStruct = Flatten<'a>;
+    type SerializeStructVariant = JsonStructVariant<'a>;
+
+    serialize_display! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), IniError> {
+        self.leaf(format_str(v.encode_utf8(&mut [0; 4])))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), IniError> {
+        self.leaf(format_str(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), IniError> {
+        self.json(None, to_value(v)?)
+    }
+
+    fn serialize_none(self) -> Result<(), IniError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), IniError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), IniError> {
+        self.leaf("null".into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), IniError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), IniError> {
+        self.leaf(format_str(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), IniError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), IniError> {
+        self.json(Some(variant), to_value(value)?)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<JsonSeq<'a>, IniError> {
+        Ok(JsonSeq {
+            ser: self,
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<JsonSeq<'a>, IniError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<JsonSeq<'a>, IniError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<JsonSeq<'a>, IniError> {
+        Ok(JsonSeq {
+            ser: self,
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Flatten<'a>, IniError> {
+        Ok(Flatten {
+            len: self.out.len(),
+            ser: self,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Flatten<'a>, IniError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<JsonStructVariant<'a>, IniError> {
+        Ok(JsonStructVariant {
+            ser: self,
+            variant,
+            fields: Map::new(),
+        })
+    }
+}
+
+/// Sequences are written as JSON.
+struct JsonSeq<'a> {
+    ser: PairsSerializer<'a>,
+    variant: Option<&'static str>,
+    items: Vec<Value>,
+}
+
+impl JsonSeq<'_> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IniError> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), IniError> {
+        self.ser.json(self.variant, Value::Array(self.items))
+    }
+}
+
+impl ser::SerializeSeq for JsonSeq<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IniError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for JsonSeq<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IniError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for JsonSeq<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IniError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for JsonSeq<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IniError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.finish()
+    }
+}
+
+/// Struct variants are written as JSON.
+struct JsonStructVariant<'a> {
+    ser: PairsSerializer<'a>,
+    variant: &'static str,
+    fields: Map<String, Value>,
+}
+
+impl ser::SerializeStructVariant for JsonStructVariant<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), IniError> {
+        self.fields.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.ser
+            .json(Some(self.variant), Value::Object(self.fields))
+    }
+}
+
+/// Structs and maps are flattened into dotted keys.
+struct Flatten<'a> {
+    ser: PairsSerializer<'a>,
+    /// The number of pairs before, to write `{}` if nothing is written.
+    len: usize,
+    key: Option<String>,
+}
+
+impl Flatten<'_> {
+    fn child(&mut self, key: &str) -> PairsSerializer<'_> {
+        PairsSerializer {
+            out: self.ser.out,
+            path: join(&self.ser.path, key),
+        }
+    }
+
+    fn finish(self) -> Result<(), IniError> {
+        // Otherwise the field would be missing
+        if self.ser.out.len() == self.len && !self.ser.path.is_empty() {
+            self.ser.out.insert(self.ser.path, "{}".into());
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Flatten<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), IniError> {
+        self.key = Some(match to_value(key)? {
+            Value::String(key) => key,
+            key @ (Value::Number(_) | Value::Bool(_)) => key.to_string(),
+            key => return Err(IniError(format!("unsupported map key {key}"))),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IniError> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_key before serialize_value");
+        value.serialize(self.child(&key))
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Flatten<'_> {
+    type Ok = ();
+    type Error = IniError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), IniError> {
+        value.serialize(self.child(key))
+    }
+
+    fn end(self) -> Result<(), IniError> {
+        self.finish()
+    }
+}
+
+/// Pairs nested by their dotted keys.
+enum Node {
+    Leaf(String),
+    /// Keys are unescaped.
+    Branch(BTreeMap<String, Node>),
+}
+
+impl Node {
+    fn from_pairs(pairs: &BTreeMap<String, String>) -> Self {
+        let mut root = Node::Branch(BTreeMap::new());
+        for (key, raw) in pairs {
+            root.insert(key.split('.').map(unescape), raw);
+        }
+        root
+    }
+
+    /// Branches win over leaves at the same key, e.g. `a.b=1` over `a={}`.
+    fn insert(&mut self, mut path: impl Iterator<Item = String>, raw: &str) {
+        match path.next() {
+            None => {
+                if let Node::Leaf(leaf) = self {
+                    *leaf = raw.to_string();
+                }
+            }
+            Some(segment) => {
+                if let Node::Leaf(_) = self {
+                    *self = Node::Branch(BTreeMap::new());
+                }
+                if let Node::Branch(children) = self {
+                    children
+                        .entry(segment)
+                        .or_insert_with(|| Node::Leaf(String::new()))
+                        .insert(path, raw);
+                }
+            }
+        }
+    }
+}
+
+struct PairsDeserializer<'a> {
+    node: &'a Node,
+    /// Escaped
+    path: String,
+    used: &'a RefCell<BTreeSet<String>>,
+}
+
+impl<'a> PairsDeserializer<'a> {
+    fn leaf(&self) -> Result<&'a str, IniError> {
+        match self.node {
+            Node::Leaf(raw) => {
+                self.used.borrow_mut().insert(self.path.clone());
+                Ok(raw)
+            }
+            Node::Branch(_) => Err(IniError(format!(
+                "{}: expected a value, found nested keys",
+                self.path
+            ))),
+        }
+    }
+
+    fn parse<T: FromStr<Err: Display>>(&self) -> Result<T, IniError> {
+        let raw = self.leaf()?;
+        raw.trim()
+            .parse()
+            .map_err(|e| IniError(format!("{}: invalid value \"{raw}\": {e}", self.path)))
+    }
+
+    fn json(&self) -> Result<Value, IniError> {
+        Ok(guess(self.leaf()?))
+    }
+}
+
+fn json_error(e: serde_json::Error) -> IniError {
+    IniError(e.to_string())
+}
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_json {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+                self.json()?.$method(visitor).map_err(json_error)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for PairsDeserializer<'_> {
+    type Error = IniError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        match self.node {
+            Node::Leaf(_) => self.json()?.deserialize_any(visitor).map_err(json_error),
+            Node::Branch(children) => visitor.visit_map(Children {
+                iter: children.iter(),
+                path: self.path,
+                used: self.used,
+                value: None,
+            }),
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    deserialize_json! {
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_seq,
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        let s = parse_str(self.leaf()?);
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(IniError(format!("{}: expected a char: \"{s}\"", self.path))),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        visitor.visit_string(parse_str(self.leaf()?))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        match self.node {
+            Node::Leaf(raw) if raw == "null" => {
+                self.leaf()?;
+                visitor.visit_none()
+            }
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        self.leaf()?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        match self.node {
+            // `{}`
+            Node::Leaf(_) => self.json()?.deserialize_map(visitor).map_err(json_error),
+            Node::Branch(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        let raw = self.leaf()?;
+        // Unit variants are written as strings
+        let value = match raw.starts_with('{') {
+            true => guess(raw),
+            false => Value::String(parse_str(raw)),
+        };
+        value
+            .deserialize_enum(name, variants, visitor)
+            .map_err(json_error)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        self.deserialize_str(visitor)
+    }
+
+    /// Not marked as used, so unknown keys are preserved.
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        visitor.visit_unit()
+    }
+}
+
+struct Children<'a> {
+    iter: btree_map::Iter<'a, String, Node>,
+    path: String,
+    used: &'a RefCell<BTreeSet<String>>,
+    value: Option<(&'a String, &'a Node)>,
+}
+
+impl<'de> MapAccess<'de> for Children<'_> {
+    type Error = IniError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, IniError> {
+        let Some((key, node)) = self.iter.next() else {
+            return Ok(None);
+        };
+        self.value = Some((key, node));
+        seed.deserialize(KeyDeserializer(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, IniError> {
+        let (key, node) = self.value.take().expect("next_key before next_value");
+        seed.deserialize(PairsDeserializer {
+            node,
+            path: join(&self.path, key),
+            used: self.used,
+        })
+    }
+}
+
+/// Map keys, e.g. `u32` keys of a `BTreeMap<u32, T>`.
+struct KeyDeserializer<'a>(&'a str);
+
+impl KeyDeserializer<'_> {
+    fn parse<T: FromStr<Err: Display>>(&self) -> Result<T, IniError> {
+        self.0
+            .parse()
+            .map_err(|e| IniError(format!("invalid key \"{}\": {e}", self.0)))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_> {
+    type Error = IniError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IniError> {
+        visitor.visit_str(self.0)
+    }
+
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, IniError> {
+        visitor.visit_enum(IntoDeserializer::<IniError>::into_deserializer(self.0))
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    #[serde(default)]
+    struct Config {
+        s: String,
+        n: u32,
+        b: bool,
+        opt: Option<String>,
+        list: Vec<u8>,
+        map: BTreeMap<String, String>,
+        ids: BTreeMap<u32, bool>,
+        theme: Theme,
+        nested: Nested,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    enum Theme {
+        #[default]
+        Light,
+        Dark,
+        Custom {
+            color: u32,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    #[serde(default)]
+    struct Nested {
+        s: String,
+        empty: BTreeMap<String, u8>,
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = Config {
+            s: "null".into(),
+            n: 7,
+            b: true,
+            opt: Some("123".into()),
+            list: vec![1, 2],
+            map: [
+                ("a.b,c=d\\".into(), "true".into()),
+                ("multi".into(), "line 1\nline 2".into()),
+                ("quoted".into(), "\"q\"".into()),
+                ("padded".into(), " x ".into()),
+            ]
+            .into(),
+            ids: [(1, true)].into(),
+            theme: Theme::Custom { color: 0xff },
+            nested: Nested {
+                s: "a=b".into(),
+                empty: BTreeMap::new(),
+            },
+        };
+        let written = to_pairs(&config).unwrap();
+        assert_eq!(written["opt"], "123");
+        assert_eq!(written["map.a%2Eb%2Cc%3Dd\\"], "true");
+        assert_eq!(written["map.multi"], r#""line 1\nline 2""#);
+        assert_eq!(written["s"], r#""null""#);
+        assert_eq!(written["nested.s"], "a=b");
+        assert_eq!(written["nested.empty"], "{}");
+        assert!(written.values().all(|value| !value.contains('\n')));
+        assert_eq!(try_from_pairs::<Config>(&written).unwrap(), config);
+
+        let config = Config {
+            theme: Theme::Dark,
+            ..Default::default()
+        };
+        let written = to_pairs(&config).unwrap();
+        assert_eq!(written["theme"], "Dark");
+        assert!(!written.contains_key("opt"));
+        assert_eq!(try_from_pairs::<Config>(&written).unwrap(), config);
+    }
+
+    /// Write and read `key=value` lines like Everything, which splits a line at the first `=` and skips comments and sections.
+    fn through_lines(pairs: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .filter(|line| !line.starts_with([';', '[']))
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keys_survive_ini_lines() {
+        let config = Config {
+            map: ["a=b", ";comment", "[section]", " padded ", "100%", "a.b,c"]
+                .map(|key| (key.to_string(), "v".to_string()))
+                .into(),
+            ..Default::default()
+        };
+        let written = to_pairs(&config).unwrap();
+        assert_eq!(written["map.a%3Db"], "v");
+        assert_eq!(written["map.%3Bcomment"], "v");
+        assert_eq!(written["map.%5Bsection%5D"], "v");
+        assert_eq!(written["map.%20padded%20"], "v");
+
+        let read = through_lines(&written);
+        assert_eq!(read, written);
+        assert_eq!(try_from_pairs::<Config>(&read).unwrap(), config);
+
+        // Not a percent-encoded byte
+        assert_eq!(unescape("100%"), "100%");
+        assert_eq!(unescape("%zz%2"), "%zz%2");
+    }
+
+    #[test]
+    fn bad_fields_fall_back_to_default() {
+        let config: Config = from_pairs(&pairs(&[
+            ("s", "Hi"),
+            ("n", "not a number"),
+            ("theme", "Unknown"),
+            ("nested.s", "nested"),
+        ]));
+        assert_eq!(
+            config,
+            Config {
+                s: "Hi".into(),
+                nested: Nested {
+                    s: "nested".into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let read = pairs(&[("s", "Hi"), ("removed", "1"), ("nested.new", "x")]);
+        let (config, used) = deserialize_tracked::<Config>(&read);
+        assert_eq!(config.unwrap().s, "Hi");
+        assert_eq!(used, BTreeSet::from(["s".to_string()]));
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod store_tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        PluginApp, PluginHandler,
+        testing::{Driver, FakeHost},
+    };
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    #[serde(default)]
+    struct Config {
+        message: String,
+        n: u32,
+    }
+
+    struct App {
+        config: Config,
+    }
+
+    impl PluginApp for App {
+        type Config = Config;
+
+        fn new(config: Option<Self::Config>) -> Self {
+            Self {
+                config: config.unwrap_or_default(),
+            }
+        }
+
+        fn config(&self) -> &Self::Config {
+            &self.config
+        }
+
+        fn into_config(self) -> Self::Config {
+            self.config
+        }
+    }
+
+    fn handler(migrations: Migrations) -> PluginHandler<App> {
+        PluginHandler::builder()
+            .name("Test Plugin")
+            .config_store(IniKeys::<Config>::new())
+            .migrations(migrations)
+            .build()
+    }
+
+    #[test]
+    fn unknown_keys_are_kept() {
+        let host = FakeHost::new();
+        host.set_setting("message", "Hi")
+            .set_setting("n", "s")
+            .set_setting("future", "1")
+            .set_setting(KEYS, "message,n,future");
+        let handler = handler(Migrations::new());
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        // Only `n` is reset
+        assert_eq!(
+            handler.with_config(|config| config.clone()),
+            Config {
+                message: "Hi".into(),
+                n: 0
+            }
+        );
+        driver.shutdown();
+
+        assert_eq!(host.setting("future").as_deref(), Some("1"));
+        assert_eq!(host.setting(KEYS).as_deref(), Some("future,message,n"));
+        assert_eq!(host.setting(VERSION), None);
+    }
+
+    #[test]
+    fn migrated_keys_are_dropped() {
+        let host = FakeHost::new();
+        host.set_setting("s", "Hi").set_setting(KEYS, "s");
+        let handler = handler(Migrations::new().then(|mut config: Value| {
+            if let Some(config) = config.as_object_mut()
+                && let Some(s) = config.remove("s")
+            {
+                config.insert("message".into(), s);
+            }
+            Ok(config)
+        }));
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        assert_eq!(handler.with_config(|config| config.message.clone()), "Hi");
+        driver.shutdown();
+
+        assert_eq!(host.setting(KEYS).as_deref(), Some("message,n"));
+        assert_eq!(host.setting(VERSION).as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn keys_are_cleared_after_uninstall() {
+        let host = FakeHost::new();
+        host.set_setting("message", "Hi")
+            .set_setting("future", "1")
+            .set_setting(KEYS, "message,future");
+        let handler = handler(Migrations::new());
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.stop();
+        driver.uninstall();
+        // Not writable yet
+        assert_eq!(host.setting("message").as_deref(), Some("Hi"));
+        driver.save_settings();
+        driver.kill();
+        drop(driver);
+
+        assert!(host.settings().values().all(String::is_empty));
+
+        let handler = self::handler(Migrations::new());
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        assert_eq!(
+            handler.with_config(|config| config.clone()),
+            Config::default()
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! Config schema versioning and migrations.
+//!
+//! Once any migration is registered with [`crate::PluginHandlerBuilder::migrations()`], config is saved in a versioned envelope:
+//! ```json
+//! {"$version":2,"config":{...}}
+//! ```
+//! `$version` marks the envelope, so configs with their own `version` and `config` fields are not mistaken for it. Configs saved without the envelope are version 0. The current version is the number of migrations, i.e. the `i`-th migration upgrades version `i` to `i + 1`.
+//!
+//! Before migrating, the original config is backed up to `Backups\{name}-v{version}{-instance_name}.{extension}` under [`crate::PluginHost::os_get_app_data_path()`], see [`super::dir::DataDir::backup_dir()`]. If loading fails, the original is also backed up and the user is notified, instead of the settings being silently reset.
+//!
+//! ## Example
+//! ```ignore
+//! PluginHandler::builder()
+//!     .name("Test Plugin")
+//!     .migrations(
+//!         Migrations::new()
+//!             // v0 -> v1: `s` renamed to `message`
+//!             .then(|mut config| {
+//!                 if let Some(config) = config.as_object_mut()
+//!                     && let Some(s) = config.remove("s")
+//!                 {
+//!                     config.insert("message".into(), s);
+//!                 }
+//!                 Ok(config)
+//!             }),
+//!     )
+//!     .build()
+//! ```
+
+use std::{fmt, fs, path::PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tracing::{debug, error, info};
+#[cfg(windows)]
+use windows_sys::Win32::UI::WindowsAndMessaging::{MB_ICONWARNING, MB_OK, MessageBoxW};
+
+use crate::{PluginApp, PluginHandler, data::format::FormatError};
+
+const VERSION_KEY: &str = "$version";
+const CONFIG_KEY: &str = "config";
+
+/// Upgrade a config by one version.
+pub trait Migrate: Send + Sync + 'static {
+    fn migrate(&self, config: Value) -> Result<Value, String>;
+}
+
+impl<F: Fn(Value) -> Result<Value, String> + Send + Sync + 'static> Migrate for F {
+    fn migrate(&self, config: Value) -> Result<Value, String> {
+        self(config)
+    }
+}
+
+#[derive(Default)]
+pub struct Migrations {
+    migrations: Vec<Box<dyn Migrate>>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the migration from the current version to the next.
+    pub fn then(mut self, migration: impl Migrate) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// The current config version.
+    pub fn version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    /// Split a stored config into its version and the config.
+    pub fn unwrap_envelope(value: Value) -> (u32, Value) {
+        match value {
+            Value::Object(mut map)
+                if map.len() == 2
+                    && map.get(VERSION_KEY).is_some_and(Value::is_u64)
+                    && map.contains_key(CONFIG_KEY) =>
+            {
+                let version = map[VERSION_KEY].as_u64().unwrap_or_default() as u32;
+                (version, map.remove(CONFIG_KEY).unwrap_or_default())
+            }
+            value => (0, value),
+        }
+    }
+
+    /// Wrap a config in the versioned envelope, unless there are no migrations.
+    pub fn wrap_envelope(&self, config: Value) -> Value {
+        if self.migrations.is_empty() {
+            return config;
+        }
+        Self::envelope(self.version(), config)
+    }
+
+    /// The versioned envelope of a config, see [`Self::unwrap_envelope()`].
+    pub fn envelope(version: u32, config: Value) -> Value {
+        let mut map = Map::new();
+        map.insert(VERSION_KEY.into(), version.into());
+        map.insert(CONFIG_KEY.into(), config);
+        Value::Object(map)
+    }
+
+    /// Upgrade `config` from `version` to [`Self::version()`].
+    pub fn migrate(&self, version: u32, mut config: Value) -> Result<Value, MigrateError> {
+        if version > self.version() {
+            return Err(MigrateError::NewerVersion {
+                version,
+                current: self.version(),
+            });
+        }
+        for (from, migration) in self.migrations.iter().enumerate().skip(version as usize) {
+            config = migration
+                .migrate(config)
+                .map_err(|error| MigrateError::Migrate {
+                    from: from as u32,
+                    error,
+                })?;
+            debug!(from, "Config migrated");
+        }
+        Ok(config)
+    }
+
+    /// Migrate, `repair` and deserialize a stored config.
+    ///
+    /// Returns the config and the stored version.
+    pub fn load<C: DeserializeOwned>(
+        &self,
+        value: Value,
+        repair: impl FnOnce(Value) -> Value,
+    ) -> Result<(C, u32), MigrateError> {
+        let (version, config) = Self::unwrap_envelope(value);
+        let config = repair(self.migrate(version, config)?);
+        let config = serde_json::from_value(config).map_err(MigrateError::Deserialize)?;
+        Ok((config, version))
+    }
+}
+
+#[derive(Debug)]
+pub enum MigrateError {
+    /// Saved by a newer version of the plugin.
+    NewerVersion {
+        version: u32,
+        current: u32,
+    },
+    /// The migration from version `from` failed.
+    Migrate {
+        from: u32,
+        error: String,
+    },
+    Parse(FormatError),
+    Deserialize(serde_json::Error),
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::NewerVersion { version, current } => write!(
+                f,
+                "config version {version} is newer than the supported version {current}"
+            ),
+            MigrateError::Migrate { from, error } => {
+                write!(f, "config migration from version {from} failed: {error}")
+            }
+            MigrateError::Parse(e) => write!(f, "config parse error: {e}"),
+            MigrateError::Deserialize(e) => write!(f, "config deserialize error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+impl<A: PluginApp> PluginHandler<A> {
+    /// Parse and migrate a serialized config, returning it with its original version.
+    pub fn parse_config_str(&self, s: &str) -> Result<(A::Config, u32), MigrateError> {
+        self.config_format
+            .parse(s)
+            .map_err(MigrateError::Parse)
+            .and_then(|value| {
+                self.migrations
+                    .load::<A::Config>(value, |config| self.config_store.repair(config))
+            })
+    }
+
+    /// Parse and migrate a stored config, backing up the original if it is migrated or fails to load.
+    pub fn load_config_str(&self, s: &str) -> Option<A::Config> {
+        match self.parse_config_str(s) {
+            Ok((config, version)) => {
+                if version != self.migrations.version() {
+                    info!(
+                        version,
+                        current = self.migrations.version(),
+                        "Config migrated"
+                    );
+                    self.backup_config(s, version);
+                }
+                Some(config)
+            }
+            Err(e) => {
+                error!(%e, "Plugin config load error");
+                let version = self
+                    .config_format
+                    .parse(s)
+                    .map(|value| Migrations::unwrap_envelope(value).0)
+                    .unwrap_or_default();
+                let backup = self.backup_config(s, version);
+                self.show_config_error(&e, backup);
+                None
+            }
+        }
+    }
+
+    fn backup_config(&self, s: &str, version: u32) -> Option<PathBuf> {
+        let dir = self.data_dir()?;
+        let path = dir.backup_dir().join(dir.instance_file_name(
+            &format!("{}-v{version}", dir.name()),
+            self.config_format.extension(),
+        ));
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, s));
+        match result {
+            Ok(()) => {
+                debug!(?path, "Config backup");
+                Some(path)
+            }
+            Err(e) => {
+                error!(?path, %e, "Config backup error");
+                None
+            }
+        }
+    }
+
+    fn show_config_error(&self, e: &MigrateError, backup: Option<PathBuf>) {
+        let name = self
+            .name
+            .as_ref()
+            .map(|name| name.get().into_owned())
+            .unwrap_or_else(|| "Everything plugin".into());
+        let mut text =
+            format!("Failed to load the settings of {name}, default settings are used.\n\n{e}");
+        if let Some(backup) = backup {
+            text.push_str(&format!(
+                "\n\nThe original settings are backed up to:\n{}",
+                backup.display()
+            ));
+        }
+        #[cfg(feature = "testing")]
+        if crate::testing::message_box(&name, &text).is_some() {
+            return;
+        }
+        message_box(&name, &text);
+    }
+}
+
+fn message_box(caption: &str, text: &str) {
+    #[cfg(windows)]
+    {
+        let text: Vec<u16> = text.encode_utf16().chain([0]).collect();
+        let caption: Vec<u16> = caption.encode_utf16().chain([0]).collect();
+        unsafe {
+            MessageBoxW(
+                0 as _,
+                text.as_ptr(),
+                caption.as_ptr(),
+                MB_OK | MB_ICONWARNING,
+            )
+        };
+    }
+    #[cfg(not(windows))]
+    let _ = (caption, text);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        message: String,
+        n: u32,
+    }
+
+    fn migrations() -> Migrations {
+        Migrations::new()
+            // v0 -> v1: `s` renamed to `message`
+            .then(|mut config: Value| {
+                let s = config["s"].take();
+                config["message"] = s;
+                Ok(config)
+            })
+            // v1 -> v2: `n` added
+            .then(|mut config: Value| {
+                config["n"] = 1.into();
+                Ok(config)
+            })
+    }
+
+    #[test]
+    fn envelope() {
+        let config = json!({"s": "Hi"});
+        let wrapped = migrations().wrap_envelope(config.clone());
+        assert_eq!(wrapped, json!({"$version": 2, "config": {"s": "Hi"}}));
+        assert_eq!(Migrations::unwrap_envelope(wrapped), (2, config.clone()));
+        assert_eq!(Migrations::new().wrap_envelope(config.clone()), config);
+    }
+
+    #[test]
+    fn unwrap_not_envelope() {
+        // A v0 config with exactly `version` and `config` fields
+        let config = json!({"version": 1, "config": {"s": "Hi"}});
+        assert_eq!(Migrations::unwrap_envelope(config.clone()), (0, config));
+    }
+
+    #[test]
+    fn load() {
+        let migrations = migrations();
+        let (config, version) = migrations
+            .load::<Config>(json!({"s": "Hi"}), |config| config)
+            .unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(
+            config,
+            Config {
+                message: "Hi".into(),
+                n: 1
+            }
+        );
+
+        let (config, version) = migrations
+            .load::<Config>(
+                Migrations::envelope(1, json!({"message": "Hi", "n": 0})),
+                |config| config,
+            )
+            .unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(config.n, 1);
+
+        let (config, version) = migrations
+            .load::<Config>(
+                migrations.wrap_envelope(json!({"message": "Hi", "n": 2})),
+                |config| config,
+            )
+            .unwrap();
+        assert_eq!((config.n, version), (2, 2));
+    }
+
+    #[test]
+    fn load_errors() {
+        let migrations = migrations();
+        assert!(matches!(
+            migrations.load::<Config>(Migrations::envelope(3, json!({})), |config| config),
+            Err(MigrateError::NewerVersion {
+                version: 3,
+                current: 2
+            })
+        ));
+
+        let failing = Migrations::new().then(|_| Err("bad".to_string()));
+        assert!(matches!(
+            failing.load::<Value>(json!({}), |config| config),
+            Err(MigrateError::Migrate { from: 0, .. })
+        ));
+
+        assert!(matches!(
+            migrations.load::<Config>(Migrations::envelope(2, json!({})), |config| config),
+            Err(MigrateError::Deserialize(_))
+        ));
+    }
+}
@@ -11,48 +11,92 @@
 //! - `os_get_(local_)?app_data_path_cat_filename`:
 //!   - Is more flexible
 //!   - Can also be used to read/write general data
-//!   - But be careful with named instances
+//!   - But be careful with named instances, see [`dir::DataDir`]
+//!
+//! See [`store`] for the available config stores.
 
 use std::{
-    ffi::{CStr, CString, c_void},
-    fmt::Debug,
+    ffi::{CString, c_void},
+    fmt::{self, Debug},
     mem::MaybeUninit,
     path::PathBuf,
+    ptr,
 };
 
 use serde::{Serialize, de::DeserializeOwned};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
+#[cfg(windows)]
+use windows_sys::Win32::System::LibraryLoader::{
+    GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS, GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+    GetModuleFileNameW, GetModuleHandleExW,
+};
 
-use crate::{PluginApp, PluginHandler, PluginHost, sys};
+use crate::{PluginApp, PluginHandler, PluginHost, data::store::StoreContext, sys};
 
 pub mod config;
+pub mod dir;
+pub mod format;
+pub mod ini;
+pub mod migrate;
+pub mod store;
+#[cfg(feature = "arc-swap")]
+pub mod swap;
+pub mod transfer;
 
 pub trait Config: Serialize + DeserializeOwned + Send + Debug + 'static {}
 
 impl<T: Serialize + DeserializeOwned + Send + Debug + 'static> Config for T {}
 
+/// A config field rejected by [`PluginApp::validate()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// The field name, e.g. `port` or `filter.regex`.
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 impl<A: PluginApp> PluginHandler<A> {
+    /// `None` before handling `EVERYTHING_PLUGIN_PM_INIT`
+    pub fn store_context(&self) -> Option<StoreContext<'_>> {
+        self.store_context_with(ptr::null_mut())
+    }
+
+    fn store_context_with(&self, data: *mut c_void) -> Option<StoreContext<'_>> {
+        Some(StoreContext::new(
+            self.data_dir()?,
+            self.config_format.extension(),
+            data,
+        ))
+    }
+
     pub fn load_settings(&self, data: *mut c_void) -> Option<A::Config> {
-        match self.get_host() {
-            Some(host) => {
-                let s = host.plugin_get_setting_string(data, "_", 0 as _);
-                if !s.is_null() {
-                    let config = unsafe { CStr::from_ptr(s as _) };
-                    debug!(
-                        config = %config.to_str().unwrap_or("Invalid UTF-8"),
-                        "Plugin config",
-                    );
-                    match serde_json::from_slice(config.to_bytes()) {
-                        Ok(config) => Some(config),
-                        Err(e) => {
-                            error!(%e, "Plugin config parse error");
-                            None
-                        }
-                    }
-                } else {
+        match self.store_context_with(data) {
+            Some(cx) => match self.config_store.load(&cx) {
+                Ok(Some(config)) => {
+                    debug!(%config, "Plugin config");
+                    self.load_config_str(&config)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    error!(%e, "Plugin config read error");
                     None
                 }
-            }
+            },
             None if !data.is_null() => {
                 // TODO: unstable Box::into_inner()
                 let config = *unsafe { Box::from_raw(data as *mut A::Config) };
@@ -64,13 +108,94 @@ impl<A: PluginApp> PluginHandler<A> {
     }
 
     pub fn save_settings(&self, data: *mut c_void) -> *mut c_void {
-        let config = unsafe { self.app() }.config();
-        let config = serde_json::to_string(config).unwrap();
+        if self.uninstalled.get() {
+            // Ini settings are only writable on save
+            debug!("Plugin save settings after uninstall");
+            self.remove_settings_with(data);
+            return 1 as _;
+        }
+
+        // Options pages may be saved without all loaded pages being saved
+        self.config_flush();
+        let config = match self.pending_config() {
+            // Stopped before the pending config is applied
+            Some(config) => serde_json::to_value(config).unwrap(),
+            None => self.with_config(|config| serde_json::to_value(config).unwrap()),
+        };
+        let config = match self
+            .config_format
+            .to_string(&self.migrations.wrap_envelope(config))
+        {
+            Ok(config) => config,
+            Err(e) => {
+                error!(%e, "Plugin config serialize error");
+                return 0 as _;
+            }
+        };
         debug!(%config, "Plugin save settings");
 
-        self.host().plugin_set_setting_string(data, "_", &config);
-        1 as _
+        let Some(cx) = self.store_context_with(data) else {
+            return 0 as _;
+        };
+        match self.config_store.save(&cx, &config) {
+            Ok(()) => 1 as _,
+            Err(e) => {
+                error!(%e, "Plugin save settings error");
+                0 as _
+            }
+        }
+    }
+
+    /// Remove the config in [`store::ConfigStore`], e.g. the `plugins.json` entry or the config file.
+    ///
+    /// Already called on [`sys::EVERYTHING_PLUGIN_PM_UNINSTALL`] by [`PluginHandler`], after [`PluginApp::uninstall()`]. Keys in `Plugins{-instance_name}.ini` are only writable on save, so they are cleared on the next [`sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS`] instead, if Everything sends one.
+    pub fn remove_settings(&self) {
+        self.remove_settings_with(ptr::null_mut());
+    }
+
+    fn remove_settings_with(&self, data: *mut c_void) {
+        let Some(cx) = self.store_context_with(data) else {
+            return;
+        };
+        if let Err(e) = self.config_store.remove(&cx) {
+            warn!(%e, "Plugin config remove error");
+        }
+        debug!("Plugin settings removed");
+    }
+}
+
+/// The file name of the plugin DLL, e.g. `MyPlugin.dll`, which is used as the ini section name.
+pub fn plugin_dll_name() -> Option<String> {
+    #[cfg(feature = "testing")]
+    if let Some(dll_name) = crate::testing::plugin_dll_name() {
+        return dll_name;
     }
+    #[cfg(windows)]
+    {
+        let mut module = 0 as _;
+        if unsafe {
+            GetModuleHandleExW(
+                GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS
+                    | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+                plugin_dll_name as *const u16,
+                &mut module,
+            )
+        } == 0
+        {
+            return None;
+        }
+
+        let mut path = [0u16; 1024];
+        let len = unsafe { GetModuleFileNameW(module, path.as_mut_ptr(), path.len() as u32) };
+        if len == 0 {
+            return None;
+        }
+        let path = PathBuf::from(String::from_utf16_lossy(&path[..len as usize]));
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+    #[cfg(not(windows))]
+    None
 }
 
 impl PluginHost {
@@ -130,7 +255,10 @@ impl PluginHost {
     ///
     /// ## Note
     /// - `data`: On [`sys::EVERYTHING_PLUGIN_PM_START`]
-    pub fn plugin_get_setting_string(
+    ///
+    /// # Safety
+    /// `data` must be the `data` of [`sys::EVERYTHING_PLUGIN_PM_START`], and `current_string` null or a valid NUL-terminated string.
+    pub unsafe fn plugin_get_setting_string(
         &self,
         data: *mut c_void,
         name: &str,
@@ -159,7 +287,10 @@ impl PluginHost {
     /// {name}={value}
     /// ...
     /// ```
-    pub fn plugin_set_setting_string(&self, data: *mut c_void, name: &str, value: &str) {
+    ///
+    /// # Safety
+    /// `data` must be the `data` of [`sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS`].
+    pub unsafe fn plugin_set_setting_string(&self, data: *mut c_void, name: &str, value: &str) {
         debug_assert!(
             !value.contains('\n'),
             "setting string value must be single-line"
@@ -178,8 +309,9 @@ impl PluginHost {
 
     /// Non-official `plugins.json` path.
     ///
-    /// TODO: Named instances
+    /// Not aware of named instances, use [`dir::DataDir::plugins_json_path()`] instead.
+    #[deprecated(note = "not aware of named instances, use `DataDir::plugins_json_path()` instead")]
     pub fn plugin_setting_json_path(&self) -> PathBuf {
-        self.os_get_app_data_path().join("plugins.json")
+        dir::DataDir::new(self, "", None, dir::InstancePolicy::Shared).plugins_json_path()
     }
 }
@@ -0,0 +1,303 @@
+//! Where config is stored, selected with [`crate::PluginHandlerBuilder::config_store()`].
+//!
+//! - [`IniKey`] (default): a single-line value in `Plugins{-instance_name}.ini`
+//! - [`PluginsJson`]: a shared `plugins{-instance_name}.json`, keyed by the plugin DLL stem
+//! - [`ConfigFile`]: a file per plugin in [`DataDir::config_dir()`], for large or hand-edited configs
+//! - [`super::ini::IniKeys`]: a key per config field in `Plugins{-instance_name}.ini`
+//!
+//! ## Example
+//! ```ignore
+//! PluginHandler::builder()
+//!     .name("Test Plugin")
+//!     .config_store(ConfigFile::default())
+//!     .build()
+//! ```
+
+use std::{
+    ffi::{CStr, OsString, c_void},
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use tracing::debug;
+
+use crate::data::{dir::DataDir, plugin_dll_name};
+
+/// Passed to [`ConfigStore`] methods.
+pub struct StoreContext<'a> {
+    pub dir: DataDir<'a>,
+    /// The file extension of [`super::format::ConfigFormat`].
+    pub extension: &'a str,
+    /// The data of [`crate::sys::EVERYTHING_PLUGIN_PM_START`] in [`ConfigStore::load()`] or [`crate::sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS`] in [`ConfigStore::save()`] and [`ConfigStore::remove()`] after uninstall, null otherwise.
+    data: *mut c_void,
+}
+
+impl<'a> StoreContext<'a> {
+    pub(crate) fn new(dir: DataDir<'a>, extension: &'a str, data: *mut c_void) -> Self {
+        Self {
+            dir,
+            extension,
+            data,
+        }
+    }
+
+    /// The plugin DLL stem, e.g. `MyPlugin` for `MyPlugin.dll`, as Everything keys the plugin's ini section by the DLL name.
+    ///
+    /// Falls back to [`DataDir::name()`] if the DLL name is unavailable.
+    pub fn key(&self) -> String {
+        plugin_dll_name()
+            .and_then(|dll_name| {
+                Path::new(&dll_name)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| self.dir.name().to_string())
+    }
+
+    /// Read a value in the plugin's section of `Plugins{-instance_name}.ini`.
+    ///
+    /// `None` if not found or outside [`ConfigStore::load()`].
+    pub fn get_setting(&self, name: &str) -> Option<String> {
+        if self.data.is_null() {
+            return None;
+        }
+        let s = unsafe {
+            self.dir
+                .host()
+                .plugin_get_setting_string(self.data, name, 0 as _)
+        };
+        if s.is_null() {
+            return None;
+        }
+        let s = unsafe { CStr::from_ptr(s as _) };
+        Some(s.to_string_lossy().into_owned())
+    }
+
+    /// Whether the plugin's ini section is accessible. Only false in [`ConfigStore::remove()`] on [`crate::sys::EVERYTHING_PLUGIN_PM_UNINSTALL`].
+    pub fn can_set_setting(&self) -> bool {
+        !self.data.is_null()
+    }
+
+    /// Write a single-line value in the plugin's section of `Plugins{-instance_name}.ini`.
+    ///
+    /// Only available in [`ConfigStore::save()`], or [`ConfigStore::remove()`] on save, see [`Self::can_set_setting()`].
+    pub fn set_setting(&self, name: &str, value: &str) -> io::Result<()> {
+        if !self.can_set_setting() {
+            return Err(io::Error::other("settings are only writable on save"));
+        }
+        if value.contains('\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ini value must be single-line",
+            ));
+        }
+        unsafe {
+            self.dir
+                .host()
+                .plugin_set_setting_string(self.data, name, value)
+        };
+        Ok(())
+    }
+}
+
+pub trait ConfigStore: Send + Sync + 'static {
+    /// Read the serialized config. `None` if not stored yet.
+    fn load(&self, cx: &StoreContext) -> io::Result<Option<String>>;
+
+    /// Write the serialized config.
+    fn save(&self, cx: &StoreContext, config: &str) -> io::Result<()>;
+
+    /// Remove the stored config on uninstall.
+    ///
+    /// Called again on [`crate::sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS`] after uninstall, where [`StoreContext::can_set_setting()`] is true. So it should be idempotent.
+    fn remove(&self, cx: &StoreContext) -> io::Result<()>;
+
+    /// Fix up the loaded config before deserializing, after migrations.
+    fn repair(&self, config: Value) -> Value {
+        config
+    }
+}
+
+/// A key in the plugin's section of `Plugins{-instance_name}.ini`, see [`crate::PluginHost::plugin_set_setting_string()`].
+///
+/// The value must be single-line. It is cleared on the first save after uninstall, and an empty value is read as not stored.
+pub struct IniKey {
+    pub key: String,
+}
+
+impl Default for IniKey {
+    fn default() -> Self {
+        Self { key: "_".into() }
+    }
+}
+
+impl ConfigStore for IniKey {
+    fn load(&self, cx: &StoreContext) -> io::Result<Option<String>> {
+        Ok(cx
+            .get_setting(&self.key)
+            .filter(|config| !config.is_empty()))
+    }
+
+    fn save(&self, cx: &StoreContext, config: &str) -> io::Result<()> {
+        cx.set_setting(&self.key, config)
+    }
+
+    fn remove(&self, cx: &StoreContext) -> io::Result<()> {
+        if !cx.can_set_setting() {
+            return Ok(());
+        }
+        cx.set_setting(&self.key, "")
+    }
+}
+
+/// A shared `plugins{-instance_name}.json` under [`crate::PluginHost::os_get_app_data_path()`], keyed by [`StoreContext::key()`].
+///
+/// JSON configs are embedded as is, others as strings.
+///
+/// Plugins update the file with a read-modify-write, locked by `plugins{-instance_name}.json.lock`.
+#[derive(Default)]
+pub struct PluginsJson;
+
+impl PluginsJson {
+    fn path(cx: &StoreContext) -> PathBuf {
+        cx.dir.plugins_json_path()
+    }
+
+    /// Lock the file against other plugins until the returned file is dropped.
+    fn lock(path: &Path) -> io::Result<File> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(with_suffix(path, ".lock"))?;
+        lock.lock()?;
+        Ok(lock)
+    }
+
+    fn read(path: &Path) -> io::Result<serde_json::Map<String, Value>> {
+        match fs::read(path) {
+            Ok(json) => match serde_json::from_slice(&json)? {
+                Value::Object(plugins) => Ok(plugins),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "plugins.json is not an object",
+                )),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(path: &Path, plugins: serde_json::Map<String, Value>) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(&Value::Object(plugins))?;
+        write_atomic(path, &json)
+    }
+}
+
+impl ConfigStore for PluginsJson {
+    fn load(&self, cx: &StoreContext) -> io::Result<Option<String>> {
+        let mut plugins = Self::read(&Self::path(cx))?;
+        Ok(plugins.remove(&cx.key()).map(|config| match config {
+            Value::String(s) => s,
+            config => config.to_string(),
+        }))
+    }
+
+    fn save(&self, cx: &StoreContext, config: &str) -> io::Result<()> {
+        let path = Self::path(cx);
+        let _lock = Self::lock(&path)?;
+        let mut plugins = Self::read(&path)?;
+        let config = serde_json::from_str(config).unwrap_or_else(|_| Value::String(config.into()));
+        plugins.insert(cx.key(), config);
+        Self::write(&path, plugins)
+    }
+
+    fn remove(&self, cx: &StoreContext) -> io::Result<()> {
+        let path = Self::path(cx);
+        let _lock = Self::lock(&path)?;
+        let mut plugins = Self::read(&path)?;
+        if plugins.remove(&cx.key()).is_some() {
+            Self::write(&path, plugins)?;
+        }
+        Ok(())
+    }
+}
+
+/// A file per plugin, `config.{extension}` in [`DataDir::config_dir()`] by default.
+#[derive(Default)]
+pub struct ConfigFile {
+    /// The file path, relative to [`crate::PluginHost::os_get_app_data_path()`] if not absolute.
+    ///
+    /// `{instance_name}` suffix is still appended to the file stem for named instances.
+    pub path: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+
+    pub fn path(&self, cx: &StoreContext) -> PathBuf {
+        match &self.path {
+            Some(path) => {
+                let path = cx.dir.host().os_get_app_data_path().join(path);
+                let stem = cx
+                    .dir
+                    .instance_stem(&path.file_stem().unwrap_or_default().to_string_lossy());
+                let file_name = match path.extension() {
+                    Some(ext) => format!("{stem}.{}", ext.to_string_lossy()),
+                    None => stem,
+                };
+                path.with_file_name(file_name)
+            }
+            None => cx.dir.config_dir().join(format!("config.{}", cx.extension)),
+        }
+    }
+}
+
+impl ConfigStore for ConfigFile {
+    fn load(&self, cx: &StoreContext) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.path(cx)) {
+            Ok(config) => Ok(Some(config)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, cx: &StoreContext, config: &str) -> io::Result<()> {
+        write_atomic(&self.path(cx), config.as_bytes())
+    }
+
+    fn remove(&self, cx: &StoreContext) -> io::Result<()> {
+        match fs::remove_file(self.path(cx)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Write to a temporary file and then rename, so a crash won't leave a truncated config.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = with_suffix(path, ".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    debug!(?path, "Config written");
+    Ok(())
+}
+
+/// `path` with `suffix` appended to the file name, e.g. `config.json.tmp`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    path.into()
+}
@@ -0,0 +1,90 @@
+//! Lock-free config snapshots with [`ArcSwapOption`], for apps accessing config from multiple threads.
+//!
+//! By default, [`crate::sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE`] stops the app, takes its config, and recreates the app with the modified config, so [`PluginHandler::with_app()`] is unavailable meanwhile.
+//!
+//! With [`ConfigSwap`] set by [`crate::PluginHandlerBuilder::config_swap()`]:
+//! - The config lives in the handler and [`PluginHandler::config()`] returns a snapshot that can be read from any thread at any time.
+//! - Options pages modify a clone of the current snapshot, which then replaces it atomically.
+//! - The app is kept and notified by [`PluginApp::config_changed()`] instead of being recreated.
+//!
+//! [`PluginApp::config()`] is only read once after [`PluginApp::new()`]; the snapshot is what is saved and passed to [`PluginApp::uninstall()`].
+//!
+//! ## Example
+//! ```ignore
+//! PluginHandler::builder()
+//!     .name("Test Plugin")
+//!     .config_swap(ConfigSwap::new())
+//!     .build()
+//!
+//! // In a background thread
+//! let config = HANDLER.config().unwrap();
+//! ```
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwapOption;
+
+use crate::{PluginApp, PluginHandler};
+
+pub struct ConfigSwap<C> {
+    config: ArcSwapOption<C>,
+    clone: fn(&C) -> C,
+}
+
+impl<C: Clone> ConfigSwap<C> {
+    pub fn new() -> Self {
+        Self {
+            config: ArcSwapOption::empty(),
+            clone: C::clone,
+        }
+    }
+}
+
+impl<C: Clone> Default for ConfigSwap<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> ConfigSwap<C> {
+    /// The current snapshot. `None` if the app is not started.
+    pub fn load(&self) -> Option<Arc<C>> {
+        self.config.load_full()
+    }
+
+    /// Replace the snapshot, returning the old one.
+    pub(crate) fn store(&self, config: C) -> (Option<Arc<C>>, Arc<C>) {
+        let config = Arc::new(config);
+        (self.config.swap(Some(config.clone())), config)
+    }
+
+    /// Replace the snapshot with a clone of `config`.
+    pub(crate) fn store_clone(&self, config: &C) {
+        self.store(self.clone_config(config));
+    }
+
+    pub(crate) fn clone_config(&self, config: &C) -> C {
+        (self.clone)(config)
+    }
+
+    /// A clone of the current snapshot.
+    pub(crate) fn load_clone(&self) -> Option<C> {
+        self.load().map(|config| (self.clone)(&config))
+    }
+
+    /// Clear the snapshot and take the config, cloning only if still referenced.
+    pub(crate) fn take(&self) -> Option<C> {
+        self.config
+            .swap(None)
+            .map(|config| Arc::try_unwrap(config).unwrap_or_else(|config| (self.clone)(&config)))
+    }
+}
+
+impl<A: PluginApp> PluginHandler<A> {
+    /// A snapshot of the current config.
+    ///
+    /// `None` if [`ConfigSwap`] is not set or the app is not started.
+    pub fn config(&self) -> Option<Arc<A::Config>> {
+        self.config_swap.as_ref().and_then(|swap| swap.load())
+    }
+}
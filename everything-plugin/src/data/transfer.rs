@@ -0,0 +1,135 @@
+//! Export the config to a file and import it back, e.g. to move settings between machines or attach them to bug reports.
+//!
+//! Exported files are serialized with [`super::format::ConfigFormat`] in the [`super::migrate`] envelope, so configs exported by older versions of the plugin are migrated on import, the same as [`PluginHandler::load_settings()`].
+//!
+//! For options pages, see [`crate::ui::winio::toolbar::ConfigToolbar`].
+//!
+//! ## Example
+//! ```ignore
+//! HANDLER.export_config(Path::new("settings.json"))?;
+//! HANDLER.import_config(Path::new("settings.json"))?;
+//! HANDLER.reset_config();
+//! ```
+
+use std::{fmt, fs, io, path::Path};
+
+use tracing::{debug, info};
+
+use crate::{
+    PluginApp, PluginHandler,
+    data::{FieldError, format::FormatError, migrate::MigrateError},
+};
+
+#[derive(Debug)]
+pub enum TransferError {
+    Io(io::Error),
+    Serialize(FormatError),
+    /// Failed to parse or migrate the imported config.
+    Migrate(MigrateError),
+    /// The imported config is rejected by [`PluginApp::validate()`].
+    Invalid(Vec<FieldError>),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::Io(e) => write!(f, "{e}"),
+            TransferError::Serialize(e) => write!(f, "config serialize error: {e}"),
+            TransferError::Migrate(e) => write!(f, "{e}"),
+            TransferError::Invalid(errors) => {
+                write!(f, "invalid config:")?;
+                for e in errors {
+                    write!(f, "\n{e}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+impl From<io::Error> for TransferError {
+    fn from(e: io::Error) -> Self {
+        TransferError::Io(e)
+    }
+}
+
+impl<A: PluginApp> PluginHandler<A> {
+    /// The file extension of exported configs, see [`super::format::ConfigFormat::extension()`].
+    pub fn config_extension(&self) -> &'static str {
+        self.config_format.extension()
+    }
+
+    /// Serialize the current config, including changes pending in options pages.
+    ///
+    /// Must be called on the thread handling plugin messages. For options pages, use [`Self::config_to_string()`] with the config being edited instead.
+    pub fn export_config_str(&self) -> Result<String, TransferError> {
+        match self.pending_config() {
+            Some(config) => self.config_to_string(config),
+            None => self.with_config(|config| self.config_to_string(config)),
+        }
+    }
+
+    /// Serialize the given config, the same as [`Self::export_config_str()`].
+    ///
+    /// Doesn't access the app, so it can be called from options page threads.
+    pub fn config_to_string(&self, config: &A::Config) -> Result<String, TransferError> {
+        let config =
+            serde_json::to_value(config).map_err(|e| TransferError::Serialize(e.into()))?;
+        self.config_format
+            .to_string(&self.migrations.wrap_envelope(config))
+            .map_err(TransferError::Serialize)
+    }
+
+    /// Must be called on the thread handling plugin messages, see [`Self::export_config_str()`].
+    pub fn export_config(&self, path: &Path) -> Result<(), TransferError> {
+        let config = self.export_config_str()?;
+        fs::write(path, config)?;
+        debug!(?path, "Config exported");
+        Ok(())
+    }
+
+    /// Write the given config to a file, like [`Self::export_config()`]. Can be called from options page threads.
+    pub fn write_config(&self, path: &Path, config: &A::Config) -> Result<(), TransferError> {
+        fs::write(path, self.config_to_string(config)?)?;
+        debug!(?path, "Config written");
+        Ok(())
+    }
+
+    /// Read, migrate and validate a config file, without applying it. Can be called from options page threads.
+    pub fn read_config(&self, path: &Path) -> Result<A::Config, TransferError> {
+        let s = fs::read_to_string(path)?;
+        let (config, version) = self.parse_config_str(&s).map_err(TransferError::Migrate)?;
+        if version != self.migrations.version() {
+            info!(
+                version,
+                current = self.migrations.version(),
+                "Imported config migrated"
+            );
+        }
+        A::validate(&config).map_err(TransferError::Invalid)?;
+        Ok(config)
+    }
+
+    /// Read a config file with [`Self::read_config()`] and apply it.
+    ///
+    /// Should not be called while options pages are open, as they would overwrite it on saving. Use [`crate::ui::winio::toolbar::ConfigToolbar`] instead.
+    pub fn import_config(&self, path: &Path) -> Result<(), TransferError> {
+        let config = self.read_config(path)?;
+        debug!(?path, ?config, "Config imported");
+        self.config_replace(config);
+        Ok(())
+    }
+
+    /// Apply the default config.
+    ///
+    /// Should not be called while options pages are open, see [`Self::import_config()`].
+    pub fn reset_config(&self)
+    where
+        A::Config: Default,
+    {
+        debug!("Config reset");
+        self.config_replace(Default::default());
+    }
+}
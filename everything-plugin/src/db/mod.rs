@@ -0,0 +1,71 @@
+//! ## Everything plugin SDK database API
+//! The database is owned by Everything. A plugin adds a local reference to it with [`Db::new()`] and runs queries against it with [`DbQuery`].
+//!
+//! Query results are updated by Everything in the background (e.g. files changed), so the result list can change at any time after a query is completed. Listen to [`DbQueryEvent::ResultsChanged`] to react to these changes.
+
+use crate::{HostError, PluginHost, sys};
+
+pub mod query;
+
+pub use query::{DbQuery, DbQueryEvent, DbQueryEvents, FindDuplicates, Search};
+
+/// A local reference to the Everything database.
+///
+/// The reference is released on drop.
+pub struct Db<'h> {
+    host: &'h PluginHost,
+    db: sys::everything_plugin_db_t,
+}
+
+impl<'h> Db<'h> {
+    /// Errors if the database API is unavailable in the running Everything version.
+    pub fn new(host: &'h PluginHost) -> Result<Self, HostError> {
+        unsafe { host.try_get::<fn()>("db_add_local_ref") }?;
+        Ok(Self {
+            host,
+            db: host.db_add_local_ref(),
+        })
+    }
+
+    pub fn host(&self) -> &'h PluginHost {
+        self.host
+    }
+
+    pub fn as_raw(&self) -> sys::everything_plugin_db_t {
+        self.db
+    }
+
+    /// Create a query on this database.
+    ///
+    /// See [`DbQuery::new()`].
+    pub fn query(&self) -> Option<(DbQuery<'_>, DbQueryEvents)> {
+        DbQuery::new(self)
+    }
+}
+
+impl Drop for Db<'_> {
+    fn drop(&mut self) {
+        unsafe { self.host.db_release(self.db) };
+    }
+}
+
+impl PluginHost {
+    /// Add a local reference to the database.
+    ///
+    /// Must be released with [`Self::db_release`].
+    pub fn db_add_local_ref(&self) -> sys::everything_plugin_db_t {
+        let db_add_local_ref: unsafe extern "system" fn() -> sys::everything_plugin_db_t =
+            unsafe { self.require("db_add_local_ref") };
+        unsafe { db_add_local_ref() }
+    }
+
+    /// Release a database reference added with [`Self::db_add_local_ref`].
+    ///
+    /// # Safety
+    /// `db` must be a reference added with [`Self::db_add_local_ref`] and not released yet.
+    pub unsafe fn db_release(&self, db: sys::everything_plugin_db_t) {
+        let db_release: unsafe extern "system" fn(db: sys::everything_plugin_db_t) =
+            unsafe { self.require("db_release") };
+        unsafe { db_release(db) };
+    }
+}
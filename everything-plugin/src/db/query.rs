@@ -0,0 +1,705 @@
+use std::{
+    cell::Cell,
+    ffi::{CString, NulError, c_void},
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bon::Builder;
+use futures_channel::mpsc;
+use futures_util::{Stream, StreamExt};
+use tracing::{debug, trace};
+
+use crate::{
+    PluginHost,
+    db::Db,
+    property::{PropertyType, PropertyValue, PropertyValueType},
+    sys,
+};
+
+/// [`sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_*`](sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_RESULTS_CHANGED)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DbQueryEvent {
+    ResultsChanged,
+    StatusChanged,
+    FileInfoChanged,
+    Ready,
+    AccessDenied,
+    QueryComplete,
+    SortComplete,
+    QueryStart,
+    SortStart,
+    OnLoaded,
+    OnIndexCancelled,
+    TreeviewChanged,
+    TreeviewPropertyChanged,
+    TreeviewSelectionChanged,
+    TreeviewCleared,
+    OfflineChanged,
+    /// Events added in newer versions of Everything.
+    Unknown(u32),
+}
+
+impl From<u32> for DbQueryEvent {
+    fn from(event: u32) -> Self {
+        match event {
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_RESULTS_CHANGED => Self::ResultsChanged,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_STATUS_CHANGED => Self::StatusChanged,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_FILE_INFO_CHANGED => Self::FileInfoChanged,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_READY => Self::Ready,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_ACCESS_DENIED => Self::AccessDenied,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_QUERY_COMPLETE => Self::QueryComplete,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_SORT_COMPLETE => Self::SortComplete,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_QUERY_START => Self::QueryStart,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_SORT_START => Self::SortStart,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_ON_LOADED => Self::OnLoaded,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_ON_INDEX_CANCELLED => Self::OnIndexCancelled,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_CHANGED => Self::TreeviewChanged,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_PROPERTY_CHANGED => {
+                Self::TreeviewPropertyChanged
+            }
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_SELECTION_CHANGED => {
+                Self::TreeviewSelectionChanged
+            }
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_CLEARED => Self::TreeviewCleared,
+            sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_OFFLINE_CHANGED => Self::OfflineChanged,
+            event => Self::Unknown(event),
+        }
+    }
+}
+
+/// [`sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_*`](sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_NONE)
+///
+/// Results are compared by the property passed along with the mode, e.g. [`PropertyType::Name`] or [`PropertyType::Size`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum FindDuplicates {
+    #[default]
+    None = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_NONE,
+    /// Only results that have at least one duplicate.
+    DuplicatedOnly = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_DUPLICATED_ONLY,
+    DuplicatedOnlyNocase = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_DUPLICATED_ONLY_NOCASE,
+    /// Only results that have no duplicates.
+    Unique = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_UNIQUE,
+    UniqueNocase = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_UNIQUE_NOCASE,
+    /// One result for each distinct value.
+    Distinct = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_DISTINCT,
+    DistinctNocase = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_DISTINCT_NOCASE,
+    /// All results except the first one of each distinct value.
+    NotDistinct = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_NOT_DISTINCT,
+    NotDistinctNocase = sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_NOT_DISTINCT_NOCASE,
+}
+
+impl FindDuplicates {
+    pub fn is_nocase(self) -> bool {
+        matches!(
+            self,
+            Self::DuplicatedOnlyNocase
+                | Self::UniqueNocase
+                | Self::DistinctNocase
+                | Self::NotDistinctNocase
+        )
+    }
+}
+
+/// ## Example
+/// ```ignore
+/// let search = Search::builder().search("*.rs").match_path(true).build();
+///
+/// // Find files with duplicate names
+/// let search = Search::builder()
+///     .search("file:")
+///     .find_duplicates(FindDuplicates::DuplicatedOnlyNocase, PropertyType::Name)
+///     .build();
+/// ```
+#[derive(Builder, Debug, Clone)]
+pub struct Search {
+    #[builder(into)]
+    search: String,
+    #[builder(default)]
+    match_case: bool,
+    #[builder(default)]
+    match_whole_word: bool,
+    #[builder(default)]
+    match_path: bool,
+    #[builder(default)]
+    match_diacritics: bool,
+    #[builder(default)]
+    match_regex: bool,
+    #[builder(default)]
+    match_prefix: bool,
+    #[builder(default)]
+    match_suffix: bool,
+    #[builder(default)]
+    ignore_punctuation: bool,
+    #[builder(default)]
+    ignore_whitespace: bool,
+    /// `(mode, property)`
+    #[builder(with = |mode: FindDuplicates, property: PropertyType| (mode, property))]
+    find_duplicates: Option<(FindDuplicates, PropertyType)>,
+}
+
+impl Search {
+    /// [`sys::EVERYTHING_PLUGIN_FILTER_FLAG_*`](sys::EVERYTHING_PLUGIN_FILTER_FLAG_CASE)
+    pub fn filter_flags(&self) -> u32 {
+        [
+            (self.match_case, sys::EVERYTHING_PLUGIN_FILTER_FLAG_CASE),
+            (
+                self.match_whole_word,
+                sys::EVERYTHING_PLUGIN_FILTER_FLAG_WHOLEWORD,
+            ),
+            (self.match_path, sys::EVERYTHING_PLUGIN_FILTER_FLAG_PATH),
+            (
+                self.match_diacritics,
+                sys::EVERYTHING_PLUGIN_FILTER_FLAG_DIACRITICS,
+            ),
+            (self.match_regex, sys::EVERYTHING_PLUGIN_FILTER_FLAG_REGEX),
+            (self.match_prefix, sys::EVERYTHING_PLUGIN_FILTER_FLAG_PREFIX),
+            (self.match_suffix, sys::EVERYTHING_PLUGIN_FILTER_FLAG_SUFFIX),
+            (
+                self.ignore_punctuation,
+                sys::EVERYTHING_PLUGIN_FILTER_FLAG_IGNORE_PUNCTUATION,
+            ),
+            (
+                self.ignore_whitespace,
+                sys::EVERYTHING_PLUGIN_FILTER_FLAG_IGNORE_WHITESPACE,
+            ),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |flags, (_, flag)| flags | flag)
+    }
+}
+
+type EventSender = mpsc::UnboundedSender<DbQueryEvent>;
+
+unsafe extern "system" fn db_query_event_proc(user_data: *mut c_void, event: u32) {
+    let tx = unsafe { &*(user_data as *const EventSender) };
+    let event = DbQueryEvent::from(event);
+    trace!(?event, "DB query event");
+    // The receiver may have been dropped if the plugin is not interested in events
+    _ = tx.unbounded_send(event);
+}
+
+/// A query on the Everything database.
+///
+/// Events of the query are delivered to the [`DbQueryEvents`] stream returned by [`DbQuery::new()`], so plugin services can `await` them instead of writing `extern "system"` callbacks.
+///
+/// The query is destroyed on drop.
+///
+/// ## Example
+/// ```ignore
+/// let db = Db::new(HANDLER.host())?;
+/// let (query, mut events) = db.query().expect("DB query create");
+/// query.search(&Search::builder().search("*.rs").build())?;
+/// events.query_complete().await;
+/// for i in 0..query.result_count() {
+///     println!("{}", query.result_name(i));
+/// }
+/// ```
+pub struct DbQuery<'a> {
+    db: &'a Db<'a>,
+    query: *mut sys::everything_plugin_db_query_t,
+    /// Boxed to have a stable address for `user_data`
+    tx: *mut EventSender,
+    find_duplicates: Cell<Option<(FindDuplicates, PropertyType)>>,
+}
+
+impl<'a> DbQuery<'a> {
+    /// Returns `None` if Everything fails to create the query.
+    pub fn new(db: &'a Db<'a>) -> Option<(Self, DbQueryEvents)> {
+        let (tx, rx) = mpsc::unbounded();
+        let tx = Box::into_raw(Box::new(tx));
+        let query = unsafe {
+            db.host()
+                .db_query_create(db.as_raw(), db_query_event_proc, tx as _)
+        };
+        debug!(?query, "DB query create");
+        if query.is_null() {
+            drop(unsafe { Box::from_raw(tx) });
+            return None;
+        }
+        Some((
+            Self {
+                db,
+                query,
+                tx,
+                find_duplicates: Cell::new(None),
+            },
+            DbQueryEvents { rx },
+        ))
+    }
+
+    fn host(&self) -> &PluginHost {
+        self.db.host()
+    }
+
+    pub fn as_raw(&self) -> *mut sys::everything_plugin_db_query_t {
+        self.query
+    }
+
+    /// Start a search.
+    ///
+    /// The search is asynchronous. Wait for [`DbQueryEvent::QueryComplete`] before reading the results.
+    ///
+    /// If [`Search::find_duplicates`] is set, results are sorted by the compared property so that duplicates are adjacent. Use [`Self::duplicate_groups()`] to get them grouped.
+    ///
+    /// Errors if the search string contains a NUL character.
+    pub fn search(&self, search: &Search) -> Result<bool, NulError> {
+        debug!(?search, "DB query search");
+        self.find_duplicates.set(search.find_duplicates);
+        let find_duplicates = match search.find_duplicates {
+            Some((mode, property)) => {
+                unsafe {
+                    self.host().db_query_sort(
+                        self.query,
+                        self.host().property_get_builtin_type(property),
+                        true,
+                    )
+                };
+                mode
+            }
+            None => FindDuplicates::None,
+        };
+        unsafe {
+            self.host().db_query_search(
+                self.query,
+                &search.search,
+                search.filter_flags(),
+                find_duplicates as u32,
+            )
+        }
+    }
+
+    pub fn result_count(&self) -> usize {
+        unsafe { self.host().db_query_get_result_count(self.query) }
+    }
+
+    pub fn result_name(&self, index: usize) -> String {
+        unsafe {
+            self.host()
+                .db_query_get_result_string("db_query_get_result_name", self.query, index)
+        }
+    }
+
+    pub fn result_path(&self, index: usize) -> String {
+        unsafe {
+            self.host()
+                .db_query_get_result_string("db_query_get_result_path", self.query, index)
+        }
+    }
+
+    /// The property value of a result, formatted as text.
+    pub fn result_property_text(&self, index: usize, property: PropertyType) -> String {
+        unsafe {
+            self.host().db_query_get_result_property_text(
+                self.query,
+                index,
+                self.host().property_get_builtin_type(property),
+            )
+        }
+    }
+
+    /// The property value of a result in typed form.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// if let PropertyValue::Size(size) = query.result_property(i, PropertyType::Size) {
+    ///     total += size;
+    /// }
+    /// ```
+    pub fn result_property(&self, index: usize, property: PropertyType) -> PropertyValue {
+        let host = self.host();
+        let raw = host.property_get_builtin_type(property);
+        match property.value_type() {
+            PropertyValueType::String => {
+                unsafe { host.db_query_get_result_property_string(self.query, index, raw) }
+                    .map_or(PropertyValue::None, PropertyValue::String)
+            }
+            ty => match unsafe { host.db_query_get_result_property_number(self.query, index, raw) }
+            {
+                None => PropertyValue::None,
+                Some(n) => match ty {
+                    PropertyValueType::Size => PropertyValue::Size(n),
+                    PropertyValueType::FileTime => PropertyValue::FileTime(n),
+                    _ => PropertyValue::Dword(n as u32),
+                },
+            },
+        }
+    }
+
+    /// Group the results of the last duplicate search by the compared property.
+    ///
+    /// Each group contains the indexes of results with the same property value, in result order. Should be called after [`DbQueryEvent::QueryComplete`].
+    ///
+    /// Without [`Search::find_duplicates`], each result is in its own group.
+    ///
+    /// ## Note
+    /// Nocase modes are grouped with a char-by-char uppercase mapping, which approximates Everything's case-insensitive comparison but is not the same.
+    /// For example, `ß` and `SS` are not grouped.
+    pub fn duplicate_groups(&self) -> Vec<Vec<usize>> {
+        let count = self.result_count();
+        let Some((mode, property)) = self.find_duplicates.get() else {
+            return (0..count).map(|i| vec![i]).collect();
+        };
+
+        let key = |i| {
+            let value = self.result_property_text(i, property);
+            if mode.is_nocase() {
+                fold_case(&value)
+            } else {
+                value
+            }
+        };
+
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut last_key = None;
+        for i in 0..count {
+            let k = key(i);
+            match groups.last_mut() {
+                Some(group) if last_key.as_ref() == Some(&k) => group.push(i),
+                _ => groups.push(vec![i]),
+            }
+            last_key = Some(k);
+        }
+        groups
+    }
+}
+
+/// Map each char to its uppercase if it is a single char, like Windows' ordinal case-insensitive comparison.
+fn fold_case(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            let mut upper = c.to_uppercase();
+            match (upper.next(), upper.next()) {
+                (Some(u), None) => u,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+impl Drop for DbQuery<'_> {
+    fn drop(&mut self) {
+        debug!(query = ?self.query, "DB query destroy");
+        unsafe { self.host().db_query_destroy(self.query) };
+        // No more events after the query is destroyed
+        drop(unsafe { Box::from_raw(self.tx) });
+    }
+}
+
+/// A stream of [`DbQueryEvent`] of a [`DbQuery`].
+///
+/// The stream ends when the query is dropped.
+pub struct DbQueryEvents {
+    rx: mpsc::UnboundedReceiver<DbQueryEvent>,
+}
+
+impl DbQueryEvents {
+    /// Wait until the given event occurs.
+    ///
+    /// Returns `false` if the query is dropped before that.
+    pub async fn wait_for(&mut self, event: DbQueryEvent) -> bool {
+        while let Some(e) = self.next().await {
+            if e == event {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Wait until [`DbQueryEvent::QueryComplete`].
+    pub async fn query_complete(&mut self) -> bool {
+        self.wait_for(DbQueryEvent::QueryComplete).await
+    }
+
+    /// Wait until [`DbQueryEvent::SortComplete`].
+    pub async fn sort_complete(&mut self) -> bool {
+        self.wait_for(DbQueryEvent::SortComplete).await
+    }
+}
+
+impl Stream for DbQueryEvents {
+    type Item = DbQueryEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next_unpin(cx)
+    }
+}
+
+impl PluginHost {
+    /// Create a query on the database.
+    ///
+    /// `event_proc` is called with `user_data` and a [`sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_*`](sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_RESULTS_CHANGED) event.
+    ///
+    /// The query must be destroyed with [`Self::db_query_destroy`]. Returns null on failure.
+    ///
+    /// # Safety
+    /// `db` must be a live reference added with [`Self::db_add_local_ref`], and `user_data` must stay valid for `event_proc` until the query is destroyed.
+    pub unsafe fn db_query_create(
+        &self,
+        db: sys::everything_plugin_db_t,
+        event_proc: unsafe extern "system" fn(user_data: *mut c_void, event: u32),
+        user_data: *mut c_void,
+    ) -> *mut sys::everything_plugin_db_query_t {
+        let db_query_create: unsafe extern "system" fn(
+            db: sys::everything_plugin_db_t,
+            event_proc: unsafe extern "system" fn(user_data: *mut c_void, event: u32),
+            user_data: *mut c_void,
+        )
+            -> *mut sys::everything_plugin_db_query_t = unsafe { self.require("db_query_create") };
+        unsafe { db_query_create(db, event_proc, user_data) }
+    }
+
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`]. It can't be used afterwards.
+    pub unsafe fn db_query_destroy(&self, query: *mut sys::everything_plugin_db_query_t) {
+        let db_query_destroy: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+        ) = unsafe { self.require("db_query_destroy") };
+        unsafe { db_query_destroy(query) };
+    }
+
+    /// Start a search.
+    ///
+    /// ## Note
+    /// - `filter_flags`: [`sys::EVERYTHING_PLUGIN_FILTER_FLAG_*`](sys::EVERYTHING_PLUGIN_FILTER_FLAG_CASE)
+    /// - `find_duplicates`: [`sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_*`](sys::EVERYTHING_PLUGIN_DB_QUERY_FIND_DUPLICATES_NONE)
+    ///
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`].
+    pub unsafe fn db_query_search(
+        &self,
+        query: *mut sys::everything_plugin_db_query_t,
+        search: &str,
+        filter_flags: u32,
+        find_duplicates: u32,
+    ) -> Result<bool, NulError> {
+        let db_query_search: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+            search_string: *const sys::everything_plugin_utf8_t,
+            filter_flags: sys::DWORD,
+            find_duplicates: sys::DWORD,
+        ) -> i32 = unsafe { self.require("db_query_search") };
+        let search = CString::new(search)?;
+        Ok(unsafe {
+            db_query_search(
+                query,
+                search.as_ptr() as _,
+                filter_flags as _,
+                find_duplicates as _,
+            ) != 0
+        })
+    }
+
+    /// Sort the results by a property.
+    ///
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`], and `property` a property from [`Self::property_get_builtin_type`] or [`Self::property_find`].
+    pub unsafe fn db_query_sort(
+        &self,
+        query: *mut sys::everything_plugin_db_query_t,
+        property: *mut sys::everything_plugin_property_t,
+        ascending: bool,
+    ) {
+        let db_query_sort: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+            property: *mut sys::everything_plugin_property_t,
+            ascending: i32,
+        ) = unsafe { self.require("db_query_sort") };
+        unsafe { db_query_sort(query, property, ascending as i32) };
+    }
+
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`].
+    pub unsafe fn db_query_get_result_count(
+        &self,
+        query: *mut sys::everything_plugin_db_query_t,
+    ) -> usize {
+        let db_query_get_result_count: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+        ) -> usize = unsafe { self.require("db_query_get_result_count") };
+        unsafe { db_query_get_result_count(query) }
+    }
+
+    unsafe fn db_query_get_result_string(
+        &self,
+        name: &str,
+        query: *mut sys::everything_plugin_db_query_t,
+        index: usize,
+    ) -> String {
+        let db_query_get_result_string: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+            index: usize,
+            cbuf: *mut sys::everything_plugin_utf8_buf_t,
+        ) = unsafe { self.require(name) };
+
+        let mut cbuf = MaybeUninit::uninit();
+        self.utf8_buf_init(cbuf.as_mut_ptr());
+
+        unsafe { db_query_get_result_string(query, index, cbuf.as_mut_ptr()) };
+
+        self.utf8_buf_into_string(cbuf.as_mut_ptr())
+    }
+
+    /// Get a property value of a result, formatted as text.
+    ///
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`], `index` less than its result count, and `property` a property from [`Self::property_get_builtin_type`] or [`Self::property_find`].
+    pub unsafe fn db_query_get_result_property_text(
+        &self,
+        query: *mut sys::everything_plugin_db_query_t,
+        index: usize,
+        property: *mut sys::everything_plugin_property_t,
+    ) -> String {
+        let db_query_get_result_property_text: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+            index: usize,
+            property: *mut sys::everything_plugin_property_t,
+            cbuf: *mut sys::everything_plugin_utf8_buf_t,
+        ) = unsafe { self.require("db_query_get_result_property_text") };
+
+        let mut cbuf = MaybeUninit::uninit();
+        self.utf8_buf_init(cbuf.as_mut_ptr());
+
+        unsafe { db_query_get_result_property_text(query, index, property, cbuf.as_mut_ptr()) };
+
+        self.utf8_buf_into_string(cbuf.as_mut_ptr())
+    }
+
+    /// Get a string property value of a result.
+    ///
+    /// Returns `None` if the value is unknown.
+    ///
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`], `index` less than its result count, and `property` a property from [`Self::property_get_builtin_type`] or [`Self::property_find`].
+    pub unsafe fn db_query_get_result_property_string(
+        &self,
+        query: *mut sys::everything_plugin_db_query_t,
+        index: usize,
+        property: *mut sys::everything_plugin_property_t,
+    ) -> Option<String> {
+        let db_query_get_result_property_string: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+            index: usize,
+            property: *mut sys::everything_plugin_property_t,
+            cbuf: *mut sys::everything_plugin_utf8_buf_t,
+        ) -> i32 = unsafe { self.require("db_query_get_result_property_string") };
+
+        let mut cbuf = MaybeUninit::uninit();
+        self.utf8_buf_init(cbuf.as_mut_ptr());
+
+        let known = unsafe {
+            db_query_get_result_property_string(query, index, property, cbuf.as_mut_ptr())
+        } != 0;
+
+        let s = self.utf8_buf_into_string(cbuf.as_mut_ptr());
+        known.then_some(s)
+    }
+
+    /// Get a size, `FILETIME` or DWORD property value of a result.
+    ///
+    /// Returns `None` if the value is unknown.
+    ///
+    /// # Safety
+    /// `query` must be a live query created by [`Self::db_query_create`], `index` less than its result count, and `property` a property from [`Self::property_get_builtin_type`] or [`Self::property_find`].
+    pub unsafe fn db_query_get_result_property_number(
+        &self,
+        query: *mut sys::everything_plugin_db_query_t,
+        index: usize,
+        property: *mut sys::everything_plugin_property_t,
+    ) -> Option<u64> {
+        let db_query_get_result_property_number: unsafe extern "system" fn(
+            query: *mut sys::everything_plugin_db_query_t,
+            index: usize,
+            property: *mut sys::everything_plugin_property_t,
+            value: *mut u64,
+        ) -> i32 = unsafe { self.require("db_query_get_result_property_number") };
+        let mut value = 0;
+        (unsafe { db_query_get_result_property_number(query, index, property, &mut value) } != 0)
+            .then_some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events() {
+        use DbQueryEvent::*;
+
+        let events = [
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_RESULTS_CHANGED,
+                ResultsChanged,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_STATUS_CHANGED,
+                StatusChanged,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_FILE_INFO_CHANGED,
+                FileInfoChanged,
+            ),
+            (sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_READY, Ready),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_ACCESS_DENIED,
+                AccessDenied,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_QUERY_COMPLETE,
+                QueryComplete,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_SORT_COMPLETE,
+                SortComplete,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_QUERY_START,
+                QueryStart,
+            ),
+            (sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_SORT_START, SortStart),
+            (sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_ON_LOADED, OnLoaded),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_ON_INDEX_CANCELLED,
+                OnIndexCancelled,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_CHANGED,
+                TreeviewChanged,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_PROPERTY_CHANGED,
+                TreeviewPropertyChanged,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_SELECTION_CHANGED,
+                TreeviewSelectionChanged,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_TREEVIEW_CLEARED,
+                TreeviewCleared,
+            ),
+            (
+                sys::EVERYTHING_PLUGIN_DB_QUERY_EVENT_OFFLINE_CHANGED,
+                OfflineChanged,
+            ),
+        ];
+        for (raw, event) in events {
+            assert_eq!(DbQueryEvent::from(raw), event, "{raw}");
+        }
+        let max = events.iter().map(|(raw, _)| *raw).max().unwrap();
+        assert_eq!(DbQueryEvent::from(max + 1), Unknown(max + 1));
+    }
+
+    #[test]
+    fn fold_case_is_char_by_char() {
+        assert_eq!(fold_case("a.txt"), fold_case("A.TXT"));
+        assert_eq!(fold_case("Ärger"), "ÄRGER");
+        // Special casing that changes the length is not applied
+        assert_eq!(fold_case("straße"), "STRAßE");
+        assert_ne!(fold_case("straße"), fold_case("STRASSE"));
+    }
+}
@@ -3,6 +3,7 @@
 //! Features:
 //! - Load and save config with [Serde](https://github.com/serde-rs/serde)
 //! - Make options pages GUI using [Winio](https://github.com/compio-rs/winio) in MVU (Elm) architecture
+//!   - Or generate them from the config struct with `#[derive(OptionsForm)]`
 //! - Internationalization with [rust-i18n](https://github.com/longbridge/rust-i18n)
 //! - Log with [tracing](https://github.com/tokio-rs/tracing)
 //!
@@ -68,6 +69,8 @@
 //!   - [`PluginHandler::init_start()`], [`PluginHandler::init_start_with_config()`]
 //!   - [`PluginHandler::stop_kill()`]
 //!   - [`PluginHandler::get_host()`]
+//! - [`testing`] (`testing` feature): run [`PluginHandler`] with a fake Everything
+//! - [`standalone`] (`winio` feature): run [`PluginApp`] and its options pages without Everything
 //!
 //! TODO:
 //! - Tray icon and menu itmes / tabs
@@ -86,36 +89,54 @@
 //! ## Debugging
 //! - `.\Everything64.exe -debug`
 //!   
-//!   Unlike `-debug`, `-debug-log` doesn't work with stdout/stderr outputs.
+//!   Unlike `-debug`, `-debug-log` doesn't work with stdout/stderr outputs. Use [`log::EverythingLayer`] (already included in [`log::tracing_try_init()`]) to write to both of them.
 //!
 //! ## Features
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![cfg_attr(feature = "doc", doc = document_features::document_features!())]
 
 use core::str;
+#[cfg(feature = "arc-swap")]
+use std::sync::Arc;
 use std::{
+    borrow::Cow,
     cell::{Cell, OnceCell, UnsafeCell},
-    ffi::{CString, c_void},
-    mem,
+    ffi::{CString, NulError, c_void},
+    fmt, mem,
     ops::Deref,
-    slice,
+    panic::{AssertUnwindSafe, catch_unwind},
+    ptr, slice,
+    sync::OnceLock,
 };
 
 use bon::Builder;
-use everything_ipc::IpcWindow;
-use tracing::{debug, trace};
+use everything_ipc::{IpcWindow, Version};
+use tracing::{debug, error, trace, warn};
 
-use crate::data::Config;
+use crate::{data::Config, lifecycle::LifecycleState};
 
 pub use everything_ipc as ipc;
+#[cfg(feature = "macros")]
+pub use everything_plugin_macros::everything_plugin;
 pub use serde;
 
 pub mod data;
+#[cfg(feature = "db")]
+pub mod db;
+pub mod lifecycle;
+pub mod localization;
 #[cfg(feature = "tracing")]
 pub mod log;
 pub mod macros;
+pub mod panic;
+pub mod property;
+#[cfg(feature = "winio")]
+pub mod standalone;
 pub mod sys;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod ui;
+pub mod version;
 
 /// ## Example
 /// ```ignore
@@ -154,9 +175,45 @@ pub trait PluginApp: 'static {
     /// Can be used to start services requiring to access the [`PluginApp`] through [`PluginHandler::with_app()`] or [`PluginHandler::app()`].
     fn start(&self) {}
 
+    /// Validate a config modified by options pages before it is applied.
+    ///
+    /// On failure, the old config is kept, the errors are sent to the options page with [`ui::OptionsPageMessage::Invalid`], and saving the page fails.
+    fn validate(config: &Self::Config) -> Result<(), Vec<data::FieldError>> {
+        _ = config;
+        Ok(())
+    }
+
+    /// Stop background services (threads, watchers, etc.) started in [`Self::start()`].
+    ///
+    /// Called on [`sys::EVERYTHING_PLUGIN_PM_STOP`], before the app is dropped on [`sys::EVERYTHING_PLUGIN_PM_KILL`].
+    fn stop(&self) {}
+
+    /// Called after an options page applied a new config, instead of recreating the app, if [`data::swap::ConfigSwap`] is set.
+    #[cfg(feature = "arc-swap")]
+    fn config_changed(&self, old: Arc<Self::Config>, new: Arc<Self::Config>) {
+        _ = (old, new);
+    }
+
+    /// Remove the plugin's own data files, e.g. caches under [`PluginHost::os_get_local_app_data_path()`].
+    ///
+    /// Called on [`sys::EVERYTHING_PLUGIN_PM_UNINSTALL`] with the final config, after [`Self::stop()`]. If the app is not created, e.g. before [`sys::EVERYTHING_PLUGIN_PM_START`], with the loaded or default config instead.
+    ///
+    /// The config in [`data::store::ConfigStore`] is removed by [`PluginHandler::remove_settings()`] afterwards. Settings will not be saved anymore.
+    fn uninstall(config: Self::Config) {
+        _ = config;
+    }
+
     fn config(&self) -> &Self::Config;
 
     fn into_config(self) -> Self::Config;
+
+    /// The level of the log file (see [`log::file`]), usually taken from the config.
+    ///
+    /// Called after the app is started.
+    #[cfg(feature = "tracing")]
+    fn log_level(&self) -> Option<tracing::level_filters::LevelFilter> {
+        None
+    }
 }
 
 /// ## Example
@@ -177,7 +234,7 @@ pub trait PluginApp: 'static {
 /// ```
 ///
 /// ## Design
-/// - Config may be accessed from multiple threads, and options pages need to modify it. To avoid race conditions, either config is cloned when modifying, and then [`PluginApp`] is reloaded with it, i.e. [`arc_swap::ArcSwap`]; or [`PluginApp`] is shutdown before modifying and then restarted.
+/// - Config may be accessed from multiple threads, and options pages need to modify it. To avoid race conditions, either config is cloned when modifying, and then [`PluginApp`] is reloaded with it, i.e. [`arc_swap::ArcSwap`] (see [`data::swap`]); or [`PluginApp`] is shutdown before modifying and then restarted.
 /// - User defined static to work around generic static limit.
 ///   - Interior mutability to make it easy to use with `static`. But `UnsafeCell` to avoid cost.
 ///
@@ -186,24 +243,25 @@ pub trait PluginApp: 'static {
 /// - May be loaded when [`sys::EVERYTHING_PLUGIN_PM_START`]
 /// - Be read when start
 /// - Be read when loading (and rendering) options pages ([`sys::EVERYTHING_PLUGIN_PM_LOAD_OPTIONS_PAGE`])
-/// - Be written/applied when [`sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE`], zero, one or multiple times
-///   - TODO: Defer
+/// - Be written when [`sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE`], zero, one or multiple times
+///   - Edits of all loaded pages are collected into one pending config, which is applied once after the last page is saved, or at [`sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS`]. So the app is restarted at most once per Apply/OK.
 /// - Be saved when [`sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS`] (can occur without prior [`sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE`])
 #[derive(Builder)]
 pub struct PluginHandler<A: PluginApp> {
     #[builder(skip)]
     host: OnceCell<PluginHost>,
 
-    #[builder(with = |x: impl Into<String>| CString::new(x.into()).unwrap())]
-    name: Option<CString>,
-    #[builder(with = |x: impl Into<String>| CString::new(x.into()).unwrap())]
-    description: Option<CString>,
-    #[builder(with = |x: impl Into<String>| CString::new(x.into()).unwrap())]
-    author: Option<CString>,
-    #[builder(with = |x: impl Into<String>| CString::new(x.into()).unwrap())]
-    version: Option<CString>,
-    #[builder(with = |x: impl Into<String>| CString::new(x.into()).unwrap())]
-    link: Option<CString>,
+    /// Metadata can follow the locale, see [`localization::LocalizedText`].
+    #[builder(into)]
+    name: Option<localization::LocalizedText>,
+    #[builder(into)]
+    description: Option<localization::LocalizedText>,
+    #[builder(into)]
+    author: Option<localization::LocalizedText>,
+    #[builder(into)]
+    version: Option<localization::LocalizedText>,
+    #[builder(into)]
+    link: Option<localization::LocalizedText>,
 
     #[builder(skip)]
     app: UnsafeCell<Option<A>>,
@@ -212,23 +270,80 @@ pub struct PluginHandler<A: PluginApp> {
     options_pages: Vec<ui::OptionsPage<A>>,
     #[builder(skip)]
     options_message: Cell<ui::OptionsMessage>,
+    /// The config being written by options pages, not applied yet.
+    #[builder(skip)]
+    pending_config: UnsafeCell<Option<A::Config>>,
+    /// The number of options pages saved into [`Self::pending_config`].
+    #[builder(skip)]
+    pending_pages: Cell<usize>,
+    /// Whether any options page changed [`Self::pending_config`].
+    #[builder(skip)]
+    pending_changed: Cell<bool>,
 
     /// TODO: Feature cfg?
     #[builder(skip)]
     instance_name: UnsafeCell<Option<String>>,
+
+    /// Keep config in lock-free snapshots instead of recreating the app on saving options pages. See [`data::swap`] for details.
+    #[cfg(feature = "arc-swap")]
+    config_swap: Option<data::swap::ConfigSwap<A::Config>>,
+
+    /// Whether named instances share the plugin's data. See [`data::dir`] for details.
+    #[builder(default)]
+    instance_policy: data::dir::InstancePolicy,
+
+    /// Where config is stored. Defaults to [`data::store::IniKey`]. See [`data::store`] for details.
+    #[builder(
+        default = Box::new(data::store::IniKey::default()),
+        with = |store: impl data::store::ConfigStore| Box::new(store),
+    )]
+    config_store: Box<dyn data::store::ConfigStore>,
+
+    /// How config is serialized. Defaults to [`data::format::Json`]. See [`data::format`] for details.
+    #[builder(
+        default = Box::new(data::format::Json::default()),
+        with = |format: impl data::format::ConfigFormat| Box::new(format),
+    )]
+    config_format: Box<dyn data::format::ConfigFormat>,
+
+    /// Config schema migrations. See [`data::migrate`] for details.
+    #[builder(default)]
+    migrations: data::migrate::Migrations,
+
+    /// Write logs to a file. See [`log::file`] for details.
+    #[cfg(feature = "tracing")]
+    file_log: Option<log::FileLog>,
+
+    /// Disable the plugin after the given number of panics, i.e. ignore all later messages, except [`panic::is_teardown()`] ones to stop the app and save the settings.
+    ///
+    /// Panics are always caught and logged, see [`panic`] for details.
+    max_panics: Option<u32>,
+    #[builder(skip)]
+    panics: Cell<u32>,
+
+    /// Only changed by [`Self::handle()`].
+    #[builder(skip)]
+    state: Cell<LifecycleState>,
+    /// Whether [`PluginApp::start()`] is called without [`PluginApp::stop()`].
+    ///
+    /// Independent of [`Self::state`], as the app is also recreated by options pages while [`LifecycleState::Started`].
+    #[builder(skip)]
+    app_running: Cell<bool>,
+    #[builder(skip)]
+    uninstalled: Cell<bool>,
 }
 
 unsafe impl<A: PluginApp> Send for PluginHandler<A> {}
 unsafe impl<A: PluginApp> Sync for PluginHandler<A> {}
 
 impl<A: PluginApp> PluginHandler<A> {
-    /// Panics if already initialized.
+    /// Reinit if already initialized, see [`lifecycle`].
     pub fn init_start(&self) {
         self.handle(sys::EVERYTHING_PLUGIN_PM_INIT, 0 as _);
         self.handle(sys::EVERYTHING_PLUGIN_PM_START, 0 as _);
     }
 
-    /// Panics if already initialized.
+    /// Reinit if already initialized, see [`lifecycle`].
     pub fn init_start_with_config(&self, config: A::Config) {
         self.handle(sys::EVERYTHING_PLUGIN_PM_INIT, 0 as _);
         self.handle(
@@ -237,7 +352,7 @@ impl<A: PluginApp> PluginHandler<A> {
         );
     }
 
-    /// Panics if not initialized or already stopped.
+    /// Messages in unexpected states are ignored, see [`lifecycle`].
     pub fn stop_kill(&self) {
         self.handle(sys::EVERYTHING_PLUGIN_PM_STOP, 0 as _);
         self.handle(sys::EVERYTHING_PLUGIN_PM_KILL, 0 as _);
@@ -249,9 +364,14 @@ impl<A: PluginApp> PluginHandler<A> {
     }
 
     /// Not available before handling `EVERYTHING_PLUGIN_PM_INIT`
+    ///
+    /// Panics if not available, use [`Self::get_host()`] if the plugin may run without Everything.
     pub fn host(&self) -> &PluginHost {
-        debug_assert!(self.get_host().is_some(), "Plugin host not inited");
-        unsafe { self.get_host().unwrap_unchecked() }
+        self.get_host().expect("Plugin host not inited")
+    }
+
+    pub fn state(&self) -> LifecycleState {
+        self.state.get()
     }
 
     #[cfg(feature = "rust-i18n")]
@@ -268,6 +388,8 @@ impl<A: PluginApp> PluginHandler<A> {
 
     /// Should be called before [`PluginHandler`] is created, as some fields may depend on the locale.
     ///
+    /// Metadata and options page names are resolved on each use instead (see [`localization::LocalizedText`]), so they follow the locale after reinit.
+    ///
     /// Already called in the [`plugin_main!`] macro. (Requiring manually calling is a footgun: [IbEverythingExt #100](https://github.com/Chaoses-Ib/IbEverythingExt/issues/100))
     pub fn handle_init_i18n(_msg: u32, _data: *mut c_void) {
         #[cfg(feature = "rust-i18n")]
@@ -288,27 +410,84 @@ impl<A: PluginApp> PluginHandler<A> {
     }
 
     /// You shouldn't and unlikely need to call this function from multiple threads.
+    ///
+    /// Panics are caught and [`panic::failure_value()`] is returned instead.
     pub fn handle(&self, msg: u32, data: *mut c_void) -> *mut c_void {
+        if self.is_disabled() && !panic::is_teardown(msg) {
+            trace!(msg = panic::message_name(msg), "Plugin disabled");
+            return panic::failure_value(msg);
+        }
+
+        let next = match self.state().next(msg) {
+            Ok(next) => next,
+            Err(e) => {
+                warn!(%e, "Plugin message ignored");
+                return panic::failure_value(msg);
+            }
+        };
+
+        match catch_unwind(AssertUnwindSafe(|| self.handle_unchecked(msg, data))) {
+            Ok(result) => {
+                // The only place changing the state, the app itself is tracked by `app_running`
+                self.state.set(next);
+                result
+            }
+            Err(payload) => {
+                let panics = self.panics.get() + 1;
+                self.panics.set(panics);
+                error!(
+                    msg = panic::message_name(msg),
+                    panics,
+                    "Plugin panicked: {}",
+                    panic::payload_message(&*payload)
+                );
+                if self.is_disabled() {
+                    error!(panics, "Plugin disabled after too many panics");
+                }
+                panic::failure_value(msg)
+            }
+        }
+    }
+
+    /// Whether the plugin is disabled by [`PluginHandlerBuilder::max_panics()`].
+    pub fn is_disabled(&self) -> bool {
+        self.max_panics
+            .is_some_and(|max_panics| self.panics.get() >= max_panics)
+    }
+
+    fn handle_unchecked(&self, msg: u32, data: *mut c_void) -> *mut c_void {
         match msg {
             sys::EVERYTHING_PLUGIN_PM_INIT => {
-                if !self.app_is_some() {
-                    #[cfg(feature = "tracing")]
-                    let _ = log::tracing_try_init();
-                    debug!("Plugin init");
-                } else {
-                    // Allow reinit (DLL hijacking and plugin)
-                    debug!("Plugin reinit");
-                    self.app_into_config();
+                match self.state() {
+                    LifecycleState::Loaded => {
+                        #[cfg(feature = "tracing")]
+                        let _ = log::tracing_try_init();
+                        debug!("Plugin init");
+                    }
+                    state => {
+                        // Allow reinit (DLL hijacking and plugin)
+                        debug!(?state, "Plugin reinit");
+                        self.app_stop();
+                        self.app_into_config();
+                    }
                 }
 
                 if !data.is_null() {
                     _ = self.host.set(unsafe { PluginHost::from_data(data) });
+                    localization::set_host(*self.host());
+                    #[cfg(feature = "tracing")]
+                    log::everything::set_host(self.host());
                 }
 
                 *unsafe { &mut *self.instance_name.get() } =
                     PluginHost::instance_name_from_main_thread();
                 debug!(instance_name = ?self.instance_name());
 
+                #[cfg(feature = "tracing")]
+                if let (Some(file_log), Some(dir)) = (&self.file_log, self.data_dir()) {
+                    log::file::init(file_log, file_log.path(&dir));
+                }
+
                 // #[cfg(feature = "rust-i18n")]
                 // rust_i18n::set_locale(&self.get_language_name());
 
@@ -318,35 +497,35 @@ impl<A: PluginApp> PluginHandler<A> {
             sys::EVERYTHING_PLUGIN_PM_GET_NAME => {
                 debug!("Plugin get name");
                 match &self.name {
-                    Some(name) => name.as_ptr() as _,
+                    Some(name) => name.get_c_str().as_ptr() as _,
                     None => 0 as _,
                 }
             }
             sys::EVERYTHING_PLUGIN_PM_GET_DESCRIPTION => {
                 debug!("Plugin get description");
                 match &self.description {
-                    Some(description) => description.as_ptr() as _,
+                    Some(description) => description.get_c_str().as_ptr() as _,
                     None => 0 as _,
                 }
             }
             sys::EVERYTHING_PLUGIN_PM_GET_AUTHOR => {
                 debug!("Plugin get author");
                 match &self.author {
-                    Some(author) => author.as_ptr() as _,
+                    Some(author) => author.get_c_str().as_ptr() as _,
                     None => 0 as _,
                 }
             }
             sys::EVERYTHING_PLUGIN_PM_GET_VERSION => {
                 debug!("Plugin get version");
                 match &self.version {
-                    Some(version) => version.as_ptr() as _,
+                    Some(version) => version.get_c_str().as_ptr() as _,
                     None => 0 as _,
                 }
             }
             sys::EVERYTHING_PLUGIN_PM_GET_LINK => {
                 debug!("Plugin get link");
                 match &self.link {
-                    Some(link) => link.as_ptr() as _,
+                    Some(link) => link.get_c_str().as_ptr() as _,
                     None => 0 as _,
                 }
             }
@@ -360,14 +539,21 @@ impl<A: PluginApp> PluginHandler<A> {
             sys::EVERYTHING_PLUGIN_PM_STOP => {
                 debug!("Plugin stop");
 
-                // TODO
+                self.app_stop();
 
                 1 as _
             }
             sys::EVERYTHING_PLUGIN_PM_UNINSTALL => {
                 debug!("Plugin uninstall");
 
-                // TODO
+                self.app_stop();
+                let config = self
+                    .app_into_config()
+                    // Not started, the app is created only for the config
+                    .unwrap_or_else(|| A::new(self.load_settings(ptr::null_mut())).into_config());
+                A::uninstall(config);
+                self.remove_settings();
+                self.uninstalled.set(true);
 
                 1 as _
             }
@@ -375,6 +561,7 @@ impl<A: PluginApp> PluginHandler<A> {
             sys::EVERYTHING_PLUGIN_PM_KILL => {
                 debug!("Plugin kill");
 
+                self.app_stop();
                 self.app_into_config();
 
                 1 as _
@@ -394,31 +581,215 @@ impl<A: PluginApp> PluginHandler<A> {
         }
     }
 
-    pub fn instance_name(&self) -> Option<&str> {
-        unsafe { &*self.instance_name.get() }.as_deref()
+    /// The plugin name, or the DLL file stem if not set or localized.
+    ///
+    /// Used for data dirs, so it doesn't follow the locale. See [`Self::plugin_display_name()`] for the localized one.
+    pub fn plugin_name(&self) -> &str {
+        static DLL_STEM: OnceLock<String> = OnceLock::new();
+        match self.name.as_ref().and_then(|name| name.as_fixed()) {
+            Some(name) => name,
+            None => DLL_STEM.get_or_init(|| {
+                let dll_name = data::plugin_dll_name().unwrap_or_default();
+                dll_name
+                    .strip_suffix(".dll")
+                    .unwrap_or(&dll_name)
+                    .to_string()
+            }),
+        }
+    }
+
+    /// The plugin name in the current locale, or [`Self::plugin_name()`] if not set.
+    pub fn plugin_display_name(&self) -> Cow<'_, str> {
+        match &self.name {
+            Some(name) => name.get(),
+            None => Cow::Borrowed(self.plugin_name()),
+        }
     }
 
-    fn app_is_some(&self) -> bool {
-        let app = unsafe { &*self.app.get() };
-        app.is_some()
+    pub fn instance_name(&self) -> Option<&str> {
+        unsafe { &*self.instance_name.get() }.as_deref()
     }
 
     fn app_new(&self, config: Option<A::Config>) {
         let app = unsafe { &mut *self.app.get() };
         debug_assert!(app.is_none(), "App already inited");
         *app = Some(A::new(config));
-        unsafe { app.as_ref().unwrap_unchecked() }.start();
+        let app = unsafe { app.as_ref().unwrap_unchecked() };
+        #[cfg(feature = "arc-swap")]
+        if let Some(swap) = &self.config_swap {
+            swap.store_clone(app.config());
+        }
+        app.start();
+        self.app_running.set(true);
+
+        #[cfg(feature = "tracing")]
+        if let Some(level) = app.log_level() {
+            log::file::set_level(level);
+        }
+    }
+
+    /// Only stop if running, even if [`sys::EVERYTHING_PLUGIN_PM_STOP`] is not sent before [`sys::EVERYTHING_PLUGIN_PM_UNINSTALL`] or [`sys::EVERYTHING_PLUGIN_PM_KILL`].
+    ///
+    /// The app may be already dropped if a config is pending, see [`Self::config_defer()`].
+    fn app_stop(&self) {
+        if self.app_running.replace(false)
+            && let Some(app) = unsafe { &*self.app.get() }
+        {
+            app.stop();
+        }
     }
 
-    fn app_into_config(&self) -> A::Config {
+    /// `None` if the app is not created. The pending config takes precedence, if any.
+    fn app_into_config(&self) -> Option<A::Config> {
+        let pending = unsafe { &mut *self.pending_config.get() }.take();
+        self.pending_pages.set(0);
         let app = unsafe { &mut *self.app.get() };
-        match app.take() {
-            Some(app) => app.into_config(),
-            None => unreachable!("App not inited"),
+        let config = app.take().map(|app| app.into_config());
+        if pending.is_some() {
+            return pending;
+        }
+        #[cfg(feature = "arc-swap")]
+        if let Some(swap) = &self.config_swap {
+            return swap.take().or(config);
+        }
+        config
+    }
+
+    /// Take the config to be modified by options pages.
+    ///
+    /// The app is stopped and dropped, unless [`data::swap::ConfigSwap`] is set.
+    fn config_take(&self) -> Option<A::Config> {
+        #[cfg(feature = "arc-swap")]
+        if let Some(swap) = &self.config_swap {
+            return swap.load_clone();
+        }
+        self.app_stop();
+        self.app_into_config()
+    }
+
+    /// Apply the config taken by [`Self::config_take()`].
+    fn config_apply(&self, config: A::Config) {
+        #[cfg(feature = "arc-swap")]
+        if let Some(swap) = &self.config_swap {
+            let (old, new) = swap.store(config);
+            if let Some(old) = old {
+                self.with_app(|app| app.config_changed(old, new));
+            }
+            return;
+        }
+        self.app_new(Some(config));
+    }
+
+    /// The config written by options pages but not applied yet.
+    pub fn pending_config(&self) -> Option<&A::Config> {
+        unsafe { &*self.pending_config.get() }.as_ref()
+    }
+
+    /// Take the config to be written by an options page, i.e. the pending config, or [`Self::config_take()`].
+    fn config_take_pending(&self) -> Option<A::Config> {
+        unsafe { &mut *self.pending_config.get() }
+            .take()
+            .or_else(|| self.config_take())
+    }
+
+    /// Keep the config written by an options page pending, until all loaded pages are saved.
+    ///
+    /// `changed` is false if the page had no changes or its changes were rejected.
+    fn config_defer(&self, config: A::Config, changed: bool) {
+        unsafe { *self.pending_config.get() = Some(config) };
+        self.pending_changed
+            .set(self.pending_changed.get() || changed);
+        let pages = self.pending_pages.get() + 1;
+        self.pending_pages.set(pages);
+
+        let loaded = self
+            .options_pages
+            .iter()
+            .filter(|page| page.is_loaded())
+            .count();
+        debug!(pages, loaded, changed, "Options page config deferred");
+        if pages >= loaded {
+            self.config_flush();
         }
     }
 
-    /// Not available during saving config and recreated afterwards. Use [`Self::with_app`] instead when possible.
+    /// Apply the pending config once, if any.
+    ///
+    /// Only applied if started. Otherwise it is kept for saving and taken by [`Self::app_into_config()`].
+    fn config_flush(&self) {
+        if self.state() != LifecycleState::Started {
+            return;
+        }
+        let Some(config) = unsafe { &mut *self.pending_config.get() }.take() else {
+            return;
+        };
+        self.pending_pages.set(0);
+        if self.pending_changed.replace(false) {
+            debug!("Options pages config applied");
+            self.config_apply(config);
+        } else {
+            self.config_restore(config);
+        }
+    }
+
+    /// Replace the config outside of options pages, discarding the pending config if any.
+    fn config_replace(&self, config: A::Config) {
+        drop(self.config_take_pending());
+        self.pending_pages.set(0);
+        self.pending_changed.set(false);
+        self.config_apply(config);
+    }
+
+    /// Restore the config taken by [`Self::config_take()`] without applying changes.
+    fn config_restore(&self, config: A::Config) {
+        #[cfg(feature = "arc-swap")]
+        if self.config_swap.is_some() {
+            // The snapshot is unchanged
+            _ = config;
+            return;
+        }
+        self.app_new(Some(config));
+    }
+
+    /// An untouched copy of the config taken by [`Self::config_take_pending()`], restored if an options page is rejected.
+    ///
+    /// `Config` is not `Clone`, so this is a JSON round trip unless [`data::swap::ConfigSwap`] is set, which resets `#[serde(skip)]` fields.
+    fn config_backup(&self, config: &A::Config) -> Option<A::Config> {
+        #[cfg(feature = "arc-swap")]
+        if let Some(swap) = &self.config_swap {
+            return Some(swap.clone_config(config));
+        }
+        match serde_json::to_value(config).and_then(serde_json::from_value) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                error!(%e, "Config backup error");
+                None
+            }
+        }
+    }
+
+    /// Discard a config rejected by an options page without a backup, together with the pending config.
+    ///
+    /// The app is recreated with the stored config instead, see [`Self::load_settings()`].
+    fn config_discard(&self, config: A::Config) {
+        drop(config);
+        self.pending_pages.set(0);
+        self.pending_changed.set(false);
+        if self.state() == LifecycleState::Started {
+            self.app_new(self.load_settings(ptr::null_mut()));
+        }
+    }
+
+    /// The latest config, i.e. the snapshot if [`data::swap::ConfigSwap`] is set.
+    pub fn with_config<T>(&self, f: impl FnOnce(&A::Config) -> T) -> T {
+        #[cfg(feature = "arc-swap")]
+        if let Some(config) = self.config() {
+            return f(&config);
+        }
+        self.with_app(|app| f(app.config()))
+    }
+
+    /// Not available during saving config (until all options pages are saved) and recreated afterwards. Use [`Self::with_app`] instead when possible.
     pub unsafe fn app(&self) -> &A {
         unsafe { &*self.app.get() }
             .as_ref()
@@ -434,15 +805,19 @@ impl<A: PluginApp> PluginHandler<A> {
 /// - [x] `instance_name` (non-official)
 /// - [x] `config_*`
 /// - [ ] `db_*`
-/// - [ ] `debug_*` (tracing)
-/// - [ ] `localization_get_*`
+///   - [x] `db_add_local_ref`, `db_release`
+///   - [x] `db_query_*` (partial)
+/// - [x] `debug_*` (tracing)
+/// - [x] `localization_get_string`
 /// - [x] `os_enable_or_disable_dlg_item`
 /// - [x] `os_get_(local_)?app_data_path_cat_filename`
 /// - [x] `plugin_?et_setting_string`
 /// - [ ] `property_*`
+///   - [x] `property_get_builtin_type`, `property_find`, `property_get_canonical_name`
 /// - [x] `ui_options_add_plugin_page`
-/// - [x] `utf8_buf_(init|kill)`
-/// - [ ] `version_get_*`, `plugin_get_version`
+/// - [x] `utf8_buf_(init|kill|copy_utf8_string)`
+/// - [x] `version_get_*`, `plugin_get_version`
+#[derive(Clone, Copy)]
 pub struct PluginHost {
     get_proc_address: sys::everything_plugin_get_proc_address_t,
 }
@@ -464,7 +839,10 @@ impl PluginHost {
         unsafe { self.get_proc_address.unwrap_unchecked() }
     }
 
-    /// You can `unwrap_unchecked()` if the API exists in all versions of Everything.
+    /// You can `unwrap_unchecked()` if the API exists in all versions of Everything. Otherwise, use [`Self::try_get()`] or [`Self::require()`].
+    ///
+    /// # Safety
+    /// `T` must be the `unsafe extern "system" fn` pointer type matching the signature of the API `name` in the SDK.
     pub unsafe fn get<T: Copy>(&self, name: &str) -> Option<T> {
         assert_eq!(mem::size_of::<T>(), mem::size_of::<fn()>());
 
@@ -479,6 +857,36 @@ impl PluginHost {
         }
     }
 
+    /// Like [`Self::get()`], but reports the running Everything version if the API is unavailable.
+    ///
+    /// # Safety
+    /// `T` must be the `unsafe extern "system" fn` pointer type matching the signature of the API `name` in the SDK.
+    pub unsafe fn try_get<T: Copy>(&self, name: &str) -> Result<T, HostError> {
+        unsafe { self.get(name) }.ok_or_else(|| HostError::Unavailable {
+            name: name.to_string(),
+            version: self
+                .is_available("version_get_major")
+                .then(|| self.version()),
+        })
+    }
+
+    /// For APIs that don't exist in all versions of Everything.
+    ///
+    /// Panics with [`HostError::Unavailable`] instead of invoking UB if the API is unavailable.
+    ///
+    /// # Safety
+    /// `T` must be the `unsafe extern "system" fn` pointer type matching the signature of the API `name` in the SDK.
+    pub unsafe fn require<T: Copy>(&self, name: &str) -> T {
+        match unsafe { self.try_get(name) } {
+            Ok(f) => f,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    pub fn is_available(&self, name: &str) -> bool {
+        unsafe { self.get::<fn()>(name) }.is_some()
+    }
+
     /// Initialize a cbuf with an empty string.
     ///
     /// The cbuf must be killed with [`Self::utf8_buf_kill`]
@@ -514,6 +922,24 @@ impl PluginHost {
         unsafe { utf8_buf_kill(cbuf) };
     }
 
+    /// Copy a string into a cbuf initialized with [`Self::utf8_buf_init`].
+    ///
+    /// # Safety
+    /// `cbuf` must be initialized with [`Self::utf8_buf_init`] and not killed yet.
+    pub unsafe fn utf8_buf_copy_utf8_string(
+        &self,
+        cbuf: *mut sys::everything_plugin_utf8_buf_t,
+        s: &str,
+    ) -> Result<(), NulError> {
+        let utf8_buf_copy_utf8_string: unsafe extern "system" fn(
+            cbuf: *mut sys::everything_plugin_utf8_buf_t,
+            s: *const sys::everything_plugin_utf8_t,
+        ) = unsafe { self.get("utf8_buf_copy_utf8_string").unwrap_unchecked() };
+        let s = CString::new(s)?;
+        unsafe { utf8_buf_copy_utf8_string(cbuf, s.as_ptr() as _) };
+        Ok(())
+    }
+
     pub fn utf8_buf_into_string(&self, cbuf: *mut sys::everything_plugin_utf8_buf_t) -> String {
         let s = unsafe { (*cbuf).to_string() };
         self.utf8_buf_kill(cbuf);
@@ -525,11 +951,50 @@ impl PluginHost {
     }
 
     pub fn instance_name_from_main_thread() -> Option<String> {
-        let ipc_window = Self::ipc_window_from_main_thread();
-        ipc_window.and_then(|w| w.instance_name().map(|s| s.to_string()))
+        #[cfg(feature = "testing")]
+        if let Some(instance_name) = testing::instance_name() {
+            return instance_name;
+        }
+        #[cfg(windows)]
+        {
+            let ipc_window = Self::ipc_window_from_main_thread();
+            ipc_window.and_then(|w| w.instance_name().map(|s| s.to_string()))
+        }
+        #[cfg(not(windows))]
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum HostError {
+    /// The API is not available in the running Everything version.
+    Unavailable {
+        name: String,
+        /// `None` if even the version API is unavailable.
+        version: Option<Version>,
+    },
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostError::Unavailable {
+                name,
+                version: Some(version),
+            } => write!(
+                f,
+                "{name} is unavailable in this Everything version ({version})"
+            ),
+            HostError::Unavailable {
+                name,
+                version: None,
+            } => write!(f, "{name} is unavailable in this Everything version"),
+        }
     }
 }
 
+impl std::error::Error for HostError {}
+
 impl Deref for sys::everything_plugin_utf8_buf_t {
     type Target = str;
 
@@ -0,0 +1,200 @@
+//! Plugin lifecycle state machine.
+//!
+//! ```text
+//! Loaded --PM_INIT--> Inited --PM_START--> Started --PM_STOP--> Stopped --PM_KILL--> Killed
+//! ```
+//! - `PM_INIT` is allowed in any state (reinit, e.g. DLL hijacking and plugin), and tears down the current app if any.
+//! - `PM_UNINSTALL` stops the app if started.
+//! - `PM_KILL` is allowed in any state except [`LifecycleState::Killed`], and stops the app if started.
+//! - Metadata messages (`PM_GET_*`) are allowed in any state.
+//!
+//! Messages in unexpected orders are logged and answered with [`crate::panic::failure_value()`] by [`crate::PluginHandler::handle()`], instead of reaching the app.
+
+use std::fmt;
+
+use crate::{panic::message_name, sys};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LifecycleState {
+    /// The DLL is loaded, but [`sys::EVERYTHING_PLUGIN_PM_INIT`] is not handled yet.
+    #[default]
+    Loaded,
+    /// The host is available.
+    Inited,
+    /// The app is created and started.
+    Started,
+    /// The app is stopped, but not dropped yet.
+    Stopped,
+    /// The app is dropped.
+    Killed,
+}
+
+impl LifecycleState {
+    /// The state after handling `msg`, or an error if `msg` is unexpected in this state.
+    pub fn next(self, msg: u32) -> Result<Self, LifecycleError> {
+        use LifecycleState::*;
+
+        let next = match (msg, self) {
+            (sys::EVERYTHING_PLUGIN_PM_INIT, _) => Some(Inited),
+
+            (
+                sys::EVERYTHING_PLUGIN_PM_GET_PLUGIN_VERSION
+                | sys::EVERYTHING_PLUGIN_PM_GET_NAME
+                | sys::EVERYTHING_PLUGIN_PM_GET_DESCRIPTION
+                | sys::EVERYTHING_PLUGIN_PM_GET_AUTHOR
+                | sys::EVERYTHING_PLUGIN_PM_GET_VERSION
+                | sys::EVERYTHING_PLUGIN_PM_GET_LINK,
+                state,
+            ) => Some(state),
+
+            (sys::EVERYTHING_PLUGIN_PM_START, Inited) => Some(Started),
+            (sys::EVERYTHING_PLUGIN_PM_STOP, Started) => Some(Stopped),
+            (sys::EVERYTHING_PLUGIN_PM_UNINSTALL, Inited | Stopped) => Some(self),
+            (sys::EVERYTHING_PLUGIN_PM_UNINSTALL, Started) => Some(Stopped),
+            (sys::EVERYTHING_PLUGIN_PM_KILL, Loaded | Inited | Started | Stopped) => Some(Killed),
+
+            // Requires the host
+            (
+                sys::EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES
+                | sys::EVERYTHING_PLUGIN_PM_GET_OPTIONS_PAGE_MINMAX
+                | sys::EVERYTHING_PLUGIN_PM_SIZE_OPTIONS_PAGE
+                | sys::EVERYTHING_PLUGIN_PM_OPTIONS_PAGE_PROC
+                | sys::EVERYTHING_PLUGIN_PM_KILL_OPTIONS_PAGE,
+                Inited | Started | Stopped,
+            ) => Some(self),
+
+            // Requires the app
+            (
+                sys::EVERYTHING_PLUGIN_PM_LOAD_OPTIONS_PAGE
+                | sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE,
+                Started,
+            ) => Some(self),
+            (sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS, Started | Stopped) => Some(self),
+
+            (
+                sys::EVERYTHING_PLUGIN_PM_START
+                | sys::EVERYTHING_PLUGIN_PM_STOP
+                | sys::EVERYTHING_PLUGIN_PM_UNINSTALL
+                | sys::EVERYTHING_PLUGIN_PM_KILL
+                | sys::EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES
+                | sys::EVERYTHING_PLUGIN_PM_LOAD_OPTIONS_PAGE
+                | sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE
+                | sys::EVERYTHING_PLUGIN_PM_GET_OPTIONS_PAGE_MINMAX
+                | sys::EVERYTHING_PLUGIN_PM_SIZE_OPTIONS_PAGE
+                | sys::EVERYTHING_PLUGIN_PM_OPTIONS_PAGE_PROC
+                | sys::EVERYTHING_PLUGIN_PM_KILL_OPTIONS_PAGE
+                | sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS,
+                _,
+            ) => None,
+
+            // Unknown messages are ignored
+            (_, state) => Some(state),
+        };
+        next.ok_or(LifecycleError::UnexpectedMessage { msg, state: self })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleError {
+    /// A `EVERYTHING_PLUGIN_PM_*` message is received in an unexpected state.
+    UnexpectedMessage { msg: u32, state: LifecycleState },
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LifecycleError::UnexpectedMessage { msg, state } => write!(
+                f,
+                "unexpected plugin message {} in state {state:?}",
+                message_name(*msg)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LifecycleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{LifecycleState::*, *};
+    use crate::sys::*;
+
+    const ALL: [LifecycleState; 5] = [Loaded, Inited, Started, Stopped, Killed];
+
+    /// `(msg, [next state from Loaded, Inited, Started, Stopped, Killed])`, `None` if unexpected
+    const TABLE: &[(u32, [Option<LifecycleState>; 5])] = &[
+        (EVERYTHING_PLUGIN_PM_INIT, [Some(Inited); 5]),
+        (
+            EVERYTHING_PLUGIN_PM_GET_NAME,
+            [
+                Some(Loaded),
+                Some(Inited),
+                Some(Started),
+                Some(Stopped),
+                Some(Killed),
+            ],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_START,
+            [None, Some(Started), None, None, None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_STOP,
+            [None, None, Some(Stopped), None, None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_UNINSTALL,
+            [None, Some(Inited), Some(Stopped), Some(Stopped), None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_KILL,
+            [Some(Killed), Some(Killed), Some(Killed), Some(Killed), None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES,
+            [None, Some(Inited), Some(Started), Some(Stopped), None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_KILL_OPTIONS_PAGE,
+            [None, Some(Inited), Some(Started), Some(Stopped), None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_LOAD_OPTIONS_PAGE,
+            [None, None, Some(Started), None, None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE,
+            [None, None, Some(Started), None, None],
+        ),
+        (
+            EVERYTHING_PLUGIN_PM_SAVE_SETTINGS,
+            [None, None, Some(Started), Some(Stopped), None],
+        ),
+        // Unknown
+        (
+            u32::MAX,
+            [
+                Some(Loaded),
+                Some(Inited),
+                Some(Started),
+                Some(Stopped),
+                Some(Killed),
+            ],
+        ),
+    ];
+
+    #[test]
+    fn next() {
+        for (msg, nexts) in TABLE {
+            for (state, next) in ALL.into_iter().zip(nexts) {
+                let expected = next.ok_or(LifecycleError::UnexpectedMessage { msg: *msg, state });
+                assert_eq!(
+                    state.next(*msg),
+                    expected,
+                    "{} in {state:?}",
+                    message_name(*msg)
+                );
+            }
+        }
+    }
+}
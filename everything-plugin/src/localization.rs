@@ -0,0 +1,430 @@
+//! ## Everything plugin SDK localization API
+//! Everything's own translations, in the language chosen by the user in Everything.
+//!
+//! Using them for common words (OK, Cancel, Name, Path, Size) keeps plugin UIs consistent with the host language.
+//!
+//! ## rust-i18n
+//! With the `rust-i18n` feature, [`EverythingBackend`] can be used to look up Everything's translations with `t!()`:
+//! ```ignore
+//! rust_i18n::i18n!("locales", backend = everything_plugin::localization::EverythingBackend::new());
+//!
+//! t!("everything.ok");
+//! t!("everything.cancel");
+//! ```
+//! Keys are [`LocalizationId::key()`] prefixed with [`EverythingBackend::PREFIX`].
+//!
+//! ## Localized metadata
+//! Plugin metadata and options page names are [`LocalizedText`]s, which can be fixed strings, closures or rust-i18n keys ([`localized!`](crate::localized)), resolved each time Everything asks for them.
+
+#[cfg(feature = "rust-i18n")]
+use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    ffi::{CStr, CString},
+    fmt,
+    sync::{Mutex, OnceLock},
+};
+
+use tracing::warn;
+
+use crate::{PluginHost, sys};
+
+/// Used by [`localized!`](crate::localized).
+#[cfg(feature = "rust-i18n")]
+#[doc(hidden)]
+pub use rust_i18n;
+
+const KEY_PREFIX: &str = "EVERYTHING_PLUGIN_LOCALIZATION_";
+
+/// The key of a constant name, e.g. `ok` for `EVERYTHING_PLUGIN_LOCALIZATION_OK`.
+const fn key_bytes<const N: usize>(name: &str) -> [u8; N] {
+    let name = name.as_bytes();
+    let mut key = [0; N];
+    let mut i = 0;
+    while i < N {
+        key[i] = name[KEY_PREFIX.len() + i].to_ascii_lowercase();
+        i += 1;
+    }
+    key
+}
+
+/// Generate [`LocalizationId`], [`LocalizationId::ALL`] and [`LocalizationId::key()`] from the sys constants.
+macro_rules! localization_ids {
+    ($($id:ident = $constant:ident,)*) => {
+        /// [`sys::EVERYTHING_PLUGIN_LOCALIZATION_*`](sys::EVERYTHING_PLUGIN_LOCALIZATION_EVERYTHING)
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(u32)]
+        pub enum LocalizationId {
+            $($id = sys::$constant,)*
+        }
+
+        impl LocalizationId {
+            pub const ALL: &[Self] = &[$(Self::$id,)*];
+
+            /// The constant name in snake case, e.g. `"ok"` for [`Self::Ok`].
+            pub fn key(self) -> &'static str {
+                match self {
+                    $(Self::$id => {
+                        const BYTES: [u8; stringify!($constant).len() - KEY_PREFIX.len()] =
+                            key_bytes(stringify!($constant));
+                        const KEY: &str = match str::from_utf8(&BYTES) {
+                            Ok(key) => key,
+                            Err(_) => panic!("non-ASCII constant name"),
+                        };
+                        KEY
+                    })*
+                }
+            }
+        }
+    };
+}
+
+localization_ids! {
+    Everything = EVERYTHING_PLUGIN_LOCALIZATION_EVERYTHING,
+    Ok = EVERYTHING_PLUGIN_LOCALIZATION_OK,
+    SearchEverything = EVERYTHING_PLUGIN_LOCALIZATION_SEARCH_EVERYTHING,
+    Name = EVERYTHING_PLUGIN_LOCALIZATION_NAME,
+    Path = EVERYTHING_PLUGIN_LOCALIZATION_PATH,
+    Size = EVERYTHING_PLUGIN_LOCALIZATION_SIZE,
+    DateModified = EVERYTHING_PLUGIN_LOCALIZATION_DATE_MODIFIED,
+    TextFiles = EVERYTHING_PLUGIN_LOCALIZATION_TEXT_FILES,
+    AllFiles = EVERYTHING_PLUGIN_LOCALIZATION_ALL_FILES,
+    Kb = EVERYTHING_PLUGIN_LOCALIZATION_KB,
+    Cancel = EVERYTHING_PLUGIN_LOCALIZATION_CANCEL,
+    EtpServer = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER,
+    HttpServer = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER,
+    EtpServerPort = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_PORT,
+    EtpServerPortHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_PORT_HELP,
+    EtpServerPassword = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_PASSWORD,
+    EtpServerPasswordHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_PASSWORD_HELP,
+    EnableEtpServerLogging = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_ETP_SERVER_LOGGING,
+    EnableEtpServerLoggingHelp = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_ETP_SERVER_LOGGING_HELP,
+    EtpLogFile = EVERYTHING_PLUGIN_LOCALIZATION_ETP_LOG_FILE,
+    EtpLogFileHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_LOG_FILE_HELP,
+    StartEtpServerOnStartup = EVERYTHING_PLUGIN_LOCALIZATION_START_ETP_SERVER_ON_STARTUP,
+    StartEtpServerOnStartupHelp = EVERYTHING_PLUGIN_LOCALIZATION_START_ETP_SERVER_ON_STARTUP_HELP,
+    BrowseForTheEtpServerLogFile = EVERYTHING_PLUGIN_LOCALIZATION_BROWSE_FOR_THE_ETP_SERVER_LOG_FILE,
+    EtpServerLogMaxSizeHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_LOG_MAX_SIZE_HELP,
+    SaveEtpServerLogFile = EVERYTHING_PLUGIN_LOCALIZATION_SAVE_ETP_SERVER_LOG_FILE,
+    HttpServerPort = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_PORT,
+    HttpServerPortHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_PORT_HELP,
+    HttpServerUsername = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_USERNAME,
+    HttpServerUsernameHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_USERNAME_HELP,
+    HttpServerPassword = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_PASSWORD,
+    HttpServerPasswordHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_PASSWORD_HELP,
+    EnableHttpServerLogging = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_HTTP_SERVER_LOGGING,
+    EnableHttpServerLoggingHelp = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_HTTP_SERVER_LOGGING_HELP,
+    HttpServerLogFile = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_LOG_FILE,
+    HttpServerLogFileHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_LOG_FILE_HELP,
+    BrowseForTheHttpServerLogFile = EVERYTHING_PLUGIN_LOCALIZATION_BROWSE_FOR_THE_HTTP_SERVER_LOG_FILE,
+    HttpServerLogMaxSize = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_LOG_MAX_SIZE,
+    SaveHttpServerLogFile = EVERYTHING_PLUGIN_LOCALIZATION_SAVE_HTTP_SERVER_LOG_FILE,
+    HttpServerAllowFileDownload = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_ALLOW_FILE_DOWNLOAD,
+    HttpServerAllowFileDownloadHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_ALLOW_FILE_DOWNLOAD_HELP,
+    EtpServerAllowFileDownload = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_ALLOW_FILE_DOWNLOAD,
+    EtpServerAllowFileDownloadHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_ALLOW_FILE_DOWNLOAD_HELP,
+    EtpServerUsername = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_USERNAME,
+    EtpServerUsernameHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_USERNAME_HELP,
+    HttpServerIndexOf = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_INDEX_OF,
+    HttpServerUpOneDirectory = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_UP_ONE_DIRECTORY,
+    HttpServerOneResultFormat = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_ONE_RESULT_FORMAT,
+    HttpServerXResultsFormat = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_X_RESULTS_FORMAT,
+    HttpServerXToYOfZResultsFormat = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_X_TO_Y_OF_Z_RESULTS_FORMAT,
+    HttpServerPrevious = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_PREVIOUS,
+    HttpServerNext = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_NEXT,
+    HttpServerBindings = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_BINDINGS,
+    HttpServerBindingsHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_BINDINGS_HELP,
+    HttpServerTitleFormat = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_TITLE_FORMAT,
+    HttpServerLogFileBrowse = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_LOG_FILE_BROWSE,
+    HttpServerServePagesFrom = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_SERVE_PAGES_FROM,
+    HttpServerServePagesFromHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_SERVE_PAGES_FROM_HELP,
+    HttpServerServePagesFromBrowse = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_SERVE_PAGES_FROM_BROWSE,
+    HttpServerServePagesFromBrowseHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_SERVE_PAGES_FROM_BROWSE_HELP,
+    HttpServerDefaultPage = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_DEFAULT_PAGE,
+    HttpServerDefaultPageHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_DEFAULT_PAGE_HELP,
+    HttpServerDefaultPageBrowse = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_DEFAULT_PAGE_BROWSE,
+    HttpServerDefaultPageBrowseHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_DEFAULT_PAGE_BROWSE_HELP,
+    HttpServerMaxLogSize = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_MAX_LOG_SIZE,
+    HttpServerRestoreDefaults = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_RESTORE_DEFAULTS,
+    HttpServerRestoreDefaultsHelp = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_RESTORE_DEFAULTS_HELP,
+    HttpServerSelectHomeCaption = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_SELECT_HOME_CAPTION,
+    HttpServerSelectDefaultPageCaption = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_SELECT_DEFAULT_PAGE_CAPTION,
+    EtpServerBindings = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_BINDINGS,
+    EtpServerBindingsHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_BINDINGS_HELP,
+    EtpServerMaxLogSize = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_MAX_LOG_SIZE,
+    EtpServerLogFileBrowse = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_LOG_FILE_BROWSE,
+    EtpServerRestoreDefaults = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_RESTORE_DEFAULTS,
+    EtpServerRestoreDefaultsHelp = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_RESTORE_DEFAULTS_HELP,
+    EtpServerErrorFormat = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_ERROR_FORMAT,
+    HttpServerErrorFormat = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_ERROR_FORMAT,
+    EtpClientErrorFormat = EVERYTHING_PLUGIN_LOCALIZATION_ETP_CLIENT_ERROR_FORMAT,
+    EtpClientDisconnectedFormat = EVERYTHING_PLUGIN_LOCALIZATION_ETP_CLIENT_DISCONNECTED_FORMAT,
+    ListenFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_LISTEN_FAILED_FORMAT,
+    BindFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_BIND_FAILED_FORMAT,
+    ConnectFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_CONNECT_FAILED_FORMAT,
+    GetaddrinfoFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_GETADDRINFO_FAILED_FORMAT,
+    ConnectionClosedByServer = EVERYTHING_PLUGIN_LOCALIZATION_CONNECTION_CLOSED_BY_SERVER,
+    SocketFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_SOCKET_FAILED_FORMAT,
+    WsastartupFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_WSASTARTUP_FAILED_FORMAT,
+    UnsupportedWsadataFormat = EVERYTHING_PLUGIN_LOCALIZATION_UNSUPPORTED_WSADATA_FORMAT,
+    RecvFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_RECV_FAILED_FORMAT,
+    SendFailedFormat = EVERYTHING_PLUGIN_LOCALIZATION_SEND_FAILED_FORMAT,
+    NoMoreAddrinfo = EVERYTHING_PLUGIN_LOCALIZATION_NO_MORE_ADDRINFO,
+    InvalidUsernameOrPassword = EVERYTHING_PLUGIN_LOCALIZATION_INVALID_USERNAME_OR_PASSWORD,
+    EnableEtpServer = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_ETP_SERVER,
+    EnableEtpServerHelp = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_ETP_SERVER_HELP,
+    EnableHttpServer = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_HTTP_SERVER,
+    EnableHttpServerHelp = EVERYTHING_PLUGIN_LOCALIZATION_ENABLE_HTTP_SERVER_HELP,
+    HttpServerDescription = EVERYTHING_PLUGIN_LOCALIZATION_HTTP_SERVER_DESCRIPTION,
+    EtpServerDescription = EVERYTHING_PLUGIN_LOCALIZATION_ETP_SERVER_DESCRIPTION,
+    EverythingServer = EVERYTHING_PLUGIN_LOCALIZATION_EVERYTHING_SERVER,
+    EverythingServerDescription = EVERYTHING_PLUGIN_LOCALIZATION_EVERYTHING_SERVER_DESCRIPTION,
+    PluginLink = EVERYTHING_PLUGIN_LOCALIZATION_PLUGIN_LINK,
+}
+
+impl LocalizationId {
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|id| id.key() == key)
+    }
+}
+
+impl PluginHost {
+    /// Get a localized string by id, in the language of Everything.
+    pub fn localization_get_string(&self, id: LocalizationId) -> String {
+        let localization_get_string: unsafe extern "system" fn(
+            id: sys::DWORD,
+        ) -> *const sys::everything_plugin_utf8_t =
+            unsafe { self.require("localization_get_string") };
+        let s = unsafe { localization_get_string(id as _) };
+        if s.is_null() {
+            return String::new();
+        }
+        unsafe { CStr::from_ptr(s as _) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+static HOST: OnceLock<PluginHost> = OnceLock::new();
+
+/// Called on [`sys::EVERYTHING_PLUGIN_PM_INIT`] to make the host available to [`EverythingBackend`] and [`LocalizedText::everything()`].
+pub(crate) fn set_host(host: PluginHost) {
+    _ = HOST.set(host);
+}
+
+/// A [`rust_i18n::Backend`] that looks up Everything's own translations.
+///
+/// Everything only provides translations in its current language, so only the current locale (see [`crate::PluginHandler::handle_init_i18n()`]) is translated. Other locales fall back to the locale files.
+#[cfg(feature = "rust-i18n")]
+#[derive(Default)]
+pub struct EverythingBackend {
+    /// `(locale, id) -> string`
+    ///
+    /// Leaked to satisfy [`rust_i18n::Backend::translate()`]'s lifetime. Bounded by the number of ids per locale.
+    cache: Mutex<HashMap<(String, LocalizationId), &'static str>>,
+}
+
+#[cfg(feature = "rust-i18n")]
+impl EverythingBackend {
+    pub const PREFIX: &str = "everything.";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "rust-i18n")]
+impl rust_i18n::Backend for EverythingBackend {
+    fn available_locales(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn translate(&self, locale: &str, key: &str) -> Option<&str> {
+        let id = LocalizationId::from_key(key.strip_prefix(Self::PREFIX)?)?;
+        if *rust_i18n::locale() != *locale {
+            return None;
+        }
+        // Not available before PM_INIT
+        let host = HOST.get()?;
+
+        let mut cache = self.cache.lock().unwrap();
+        let s = cache
+            .entry((locale.to_string(), id))
+            .or_insert_with(|| host.localization_get_string(id).leak());
+        Some(s)
+    }
+}
+
+/// A string that can follow the current locale, for plugin metadata and [`crate::ui::OptionsPage`] names.
+///
+/// Resolved on each [`sys::EVERYTHING_PLUGIN_PM_GET_NAME`], [`sys::EVERYTHING_PLUGIN_PM_GET_DESCRIPTION`], [`sys::EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES`], etc., so the plugin list follows Everything's language setting even after the locale is reinited (see [`crate::PluginHandler::handle_init_i18n()`]).
+///
+/// ## Example
+/// ```ignore
+/// PluginHandler::builder()
+///     // Fixed
+///     .name("Test Plugin")
+///     // rust-i18n key, with the `rust-i18n` feature
+///     .description(localized!("plugin.description"))
+///     // Closure
+///     .author(LocalizedText::from_fn(|| t!("plugin.author").into_owned()))
+/// ```
+pub struct LocalizedText {
+    source: TextSource,
+    /// Everything keeps the returned pointers, so resolved strings are kept until `self` is dropped.
+    ///
+    /// Deduplicated by content, so bounded by the number of distinct texts `f` returns, e.g. one per locale for translations. Closures returning ever-changing texts grow it on each use.
+    cache: Mutex<Vec<CString>>,
+}
+
+enum TextSource {
+    Fixed(CString),
+    Fn(Box<dyn Fn() -> String + Send + Sync>),
+}
+
+impl LocalizedText {
+    /// Panics if `text` contains NUL.
+    pub fn fixed(text: impl Into<String>) -> Self {
+        Self::new(TextSource::Fixed(CString::new(text.into()).unwrap()))
+    }
+
+    /// `f` is called on each use with the current locale set. See also [`localized!`](crate::localized).
+    pub fn from_fn(f: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        Self::new(TextSource::Fn(Box::new(f)))
+    }
+
+    /// Everything's own translation of `id`, or `fallback` if not available, e.g. outside Everything.
+    pub fn everything(id: LocalizationId, fallback: &'static str) -> Self {
+        Self::from_fn(move || {
+            HOST.get()
+                .filter(|host| host.is_available("localization_get_string"))
+                .map(|host| host.localization_get_string(id))
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| fallback.into())
+        })
+    }
+
+    fn new(source: TextSource) -> Self {
+        Self {
+            source,
+            cache: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// `None` if the text follows the locale.
+    pub fn as_fixed(&self) -> Option<&str> {
+        match &self.source {
+            TextSource::Fixed(text) => text.to_str().ok(),
+            TextSource::Fn(_) => None,
+        }
+    }
+
+    /// Resolve against the current locale.
+    pub fn get(&self) -> Cow<'_, str> {
+        match &self.source {
+            TextSource::Fixed(text) => text.to_string_lossy(),
+            TextSource::Fn(f) => Cow::Owned(f()),
+        }
+    }
+
+    /// Resolve against the current locale. The pointer stays valid for the lifetime of `self`.
+    pub fn get_c_str(&self) -> &CStr {
+        let f = match &self.source {
+            TextSource::Fixed(text) => return text,
+            TextSource::Fn(f) => f,
+        };
+        let text = f();
+        let text = CString::new(text).unwrap_or_else(|e| {
+            warn!(%e, "Localized text contains NUL");
+            let end = e.nul_position();
+            let mut text = e.into_vec();
+            text.truncate(end);
+            CString::new(text).unwrap_or_default()
+        });
+
+        let mut cache = self.cache.lock().unwrap();
+        let cached = match cache.iter().find(|cached| **cached == text) {
+            Some(cached) => cached,
+            None => {
+                cache.push(text);
+                cache.last().unwrap()
+            }
+        };
+        // Entries are never removed and their buffers don't move when the `Vec` grows
+        unsafe { &*(cached.as_c_str() as *const CStr) }
+    }
+}
+
+impl From<&str> for LocalizedText {
+    fn from(text: &str) -> Self {
+        Self::fixed(text)
+    }
+}
+
+impl From<String> for LocalizedText {
+    fn from(text: String) -> Self {
+        Self::fixed(text)
+    }
+}
+
+impl From<&String> for LocalizedText {
+    fn from(text: &String) -> Self {
+        Self::fixed(text.as_str())
+    }
+}
+
+/// Fixed, e.g. `t!()` called once. Use [`localized!`](crate::localized) to follow the locale.
+impl From<Cow<'_, str>> for LocalizedText {
+    fn from(text: Cow<'_, str>) -> Self {
+        Self::fixed(text)
+    }
+}
+
+impl fmt::Debug for LocalizedText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            TextSource::Fixed(text) => f.debug_tuple("Fixed").field(text).finish(),
+            TextSource::Fn(_) => f.debug_tuple("Fn").field(&self.get()).finish(),
+        }
+    }
+}
+
+/// A [`LocalizedText`] from a rust-i18n key, translated with the plugin's `t!()` on each use.
+///
+/// ```ignore
+/// rust_i18n::i18n!("locales");
+///
+/// PluginHandler::builder()
+///     .name(localized!("plugin.name"))
+///     .description(localized!("plugin.description"))
+/// ```
+#[cfg(feature = "rust-i18n")]
+#[macro_export]
+macro_rules! localized {
+    ($key:literal) => {
+        ::everything_plugin::localization::LocalizedText::from_fn(|| {
+            ::everything_plugin::localization::rust_i18n::t!($key).into_owned()
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        assert_eq!(LocalizationId::Ok.key(), "ok");
+        assert_eq!(
+            LocalizationId::Ok as u32,
+            sys::EVERYTHING_PLUGIN_LOCALIZATION_OK
+        );
+        assert_eq!(
+            LocalizationId::HttpServerXToYOfZResultsFormat.key(),
+            "http_server_x_to_y_of_z_results_format"
+        );
+        for &id in LocalizationId::ALL {
+            assert_eq!(LocalizationId::from_key(id.key()), Some(id));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! A [`Layer`] that writes to Everything's debug console and log file.
+//!
+//! - `.\Everything64.exe -debug`: debug console
+//! - `.\Everything64.exe -debug-log`: `Everything-debug.log`
+//!
+//! Unlike stdout/stderr outputs, this works with both of them.
+//!
+//! Events before [`sys::EVERYTHING_PLUGIN_PM_INIT`] (i.e. before the host is available) are buffered and written once the host is set.
+
+use std::{
+    collections::VecDeque,
+    ffi::CString,
+    fmt::{self, Write},
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use tracing::{Event, Level, Subscriber, field::Field};
+use tracing_subscriber::{Layer, field::Visit, layer::Context};
+
+use crate::{PluginHost, sys};
+
+/// Max number of buffered events before the host is set. Older events are dropped.
+const BUFFER_CAPACITY: usize = 1024;
+
+/// Console text attributes
+mod color {
+    pub const GRAY: u32 = 0x08;
+    pub const GREEN: u32 = 0x0A;
+    pub const RED: u32 = 0x0C;
+    pub const YELLOW: u32 = 0x0E;
+    pub const WHITE: u32 = 0x0F;
+}
+
+type DebugColorPrintf =
+    unsafe extern "C" fn(color: sys::DWORD, format: *const sys::everything_plugin_utf8_t, ...);
+type DebugPrintf = unsafe extern "C" fn(format: *const sys::everything_plugin_utf8_t, ...);
+
+#[derive(Clone, Copy)]
+enum Printf {
+    Color(DebugColorPrintf),
+    /// Fallback if `debug_color_printf` is unavailable.
+    Plain(DebugPrintf),
+}
+
+/// Only set while holding [`BUFFER`], so no event is buffered after it is drained.
+static PRINTF: OnceLock<Printf> = OnceLock::new();
+static BUFFER: Mutex<VecDeque<(Level, String)>> = Mutex::new(VecDeque::new());
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether events are being written to Everything, i.e. the host has been set.
+///
+/// Used to avoid duplicated outputs on the `-debug` console, which is also stderr.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Start writing to Everything, including the buffered events.
+///
+/// Already called on [`sys::EVERYTHING_PLUGIN_PM_INIT`] by [`crate::PluginHandler`].
+pub fn set_host(host: &PluginHost) {
+    // Resolve before any event is written, as `PluginHost::get()` itself logs
+    let printf = match unsafe { host.get::<DebugColorPrintf>("debug_color_printf") } {
+        Some(debug_color_printf) => Printf::Color(debug_color_printf),
+        None => match unsafe { host.get::<DebugPrintf>("debug_printf") } {
+            Some(debug_printf) => Printf::Plain(debug_printf),
+            None => return,
+        },
+    };
+
+    let mut buffer = BUFFER.lock().unwrap();
+    if PRINTF.set(printf).is_err() {
+        // Reinit
+        return;
+    }
+    for (level, line) in buffer.drain(..) {
+        print(printf, level, &line);
+    }
+    drop(buffer);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn write(level: Level, line: &str) {
+    let printf = match PRINTF.get() {
+        Some(printf) => *printf,
+        None => {
+            let mut buffer = BUFFER.lock().unwrap();
+            // The host may be set meanwhile
+            match PRINTF.get() {
+                Some(printf) => *printf,
+                None => {
+                    if buffer.len() == BUFFER_CAPACITY {
+                        buffer.pop_front();
+                    }
+                    buffer.push_back((level, line.to_string()));
+                    return;
+                }
+            }
+        }
+    };
+    print(printf, level, line);
+}
+
+fn print(printf: Printf, level: Level, line: &str) {
+    let Ok(line) = CString::new(format!("{line}\n")) else {
+        return;
+    };
+    match printf {
+        Printf::Color(debug_color_printf) => {
+            let color = match level {
+                Level::ERROR => color::RED,
+                Level::WARN => color::YELLOW,
+                Level::INFO => color::GREEN,
+                Level::DEBUG => color::WHITE,
+                Level::TRACE => color::GRAY,
+            };
+            unsafe { debug_color_printf(color as _, c"%s".as_ptr() as _, line.as_ptr()) };
+        }
+        Printf::Plain(debug_printf) => unsafe { debug_printf(c"%s".as_ptr() as _, line.as_ptr()) },
+    }
+}
+
+/// ## Example
+/// ```ignore
+/// tracing_subscriber::registry()
+///     .with(EverythingLayer)
+///     .init();
+/// ```
+pub struct EverythingLayer;
+
+impl<S: Subscriber> Layer<S> for EverythingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = LineVisitor {
+            line: format!("{:>5} {}: ", metadata.level(), metadata.target()),
+            fields: String::new(),
+        };
+        event.record(&mut visitor);
+        let LineVisitor { mut line, fields } = visitor;
+        line.push_str(&fields);
+        write(*metadata.level(), &line);
+    }
+}
+
+struct LineVisitor {
+    line: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            _ = write!(self.line, "{value:?}");
+        } else {
+            _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.line.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
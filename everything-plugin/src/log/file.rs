@@ -0,0 +1,343 @@
+//! Persistent log files with rotation, so users can send logs from release builds.
+//!
+//! Opt-in with [`crate::PluginHandlerBuilder::file_log()`]. Files are written to `Logs\{name}.log` under [`crate::PluginHost::os_get_local_app_data_path()`], or `Logs\{name}-{instance_name}.log` for [named instances](https://www.voidtools.com/support/everything/multiple_instances/#named_instances) (see [`DataDir`]).
+//!
+//! Level (from high to low priority):
+//! 1. The environment variable [`FileLog::env`], e.g. `EVERYTHING_PLUGIN_LOG=trace`
+//! 2. [`crate::PluginApp::log_level()`]
+//! 3. [`FileLog::level`]
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        Mutex,
+        atomic::{AtomicU8, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bon::Builder;
+use tracing::{Level, Metadata, debug, level_filters::LevelFilter, warn};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::data::dir::DataDir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Rotate when the date (UTC) changes.
+    Daily,
+    /// Rotate when the file would exceed the size in bytes.
+    Size(u64),
+}
+
+/// ## Example
+/// ```ignore
+/// PluginHandler::builder()
+///     .name("Test Plugin")
+///     .file_log(FileLog::builder().rotation(Rotation::Size(1024 * 1024)).build())
+///     .build()
+/// ```
+#[derive(Builder, Debug, Clone)]
+pub struct FileLog {
+    /// The file name without extension. Defaults to the plugin name.
+    #[builder(into)]
+    name: Option<String>,
+    #[builder(default = Rotation::Daily)]
+    rotation: Rotation,
+    /// Max number of rotated files to keep, besides the current one.
+    #[builder(default = 7)]
+    max_files: usize,
+    /// The environment variable to read the level from.
+    #[builder(into, default = "EVERYTHING_PLUGIN_LOG")]
+    env: String,
+    #[builder(default = LevelFilter::INFO)]
+    level: LevelFilter,
+}
+
+impl FileLog {
+    pub fn path(&self, dir: &DataDir) -> PathBuf {
+        let name = self.name.as_deref().unwrap_or(dir.name());
+        dir.log_dir().join(dir.instance_file_name(name, "log"))
+    }
+}
+
+static FILE: Mutex<Option<RollingFile>> = Mutex::new(None);
+
+/// [`LevelFilter`] encoded by [`level_to_u8`]. [`LevelFilter::OFF`] until [`init()`].
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+/// Whether the level is overridden by the environment variable.
+static LEVEL_FROM_ENV: AtomicU8 = AtomicU8::new(0);
+
+fn level_to_u8(level: LevelFilter) -> u8 {
+    match level.into_level() {
+        None => 0,
+        Some(Level::ERROR) => 1,
+        Some(Level::WARN) => 2,
+        Some(Level::INFO) => 3,
+        Some(Level::DEBUG) => 4,
+        Some(Level::TRACE) => 5,
+    }
+}
+
+fn level_from_u8(level: u8) -> LevelFilter {
+    match level {
+        0 => LevelFilter::OFF,
+        1 => LevelFilter::ERROR,
+        2 => LevelFilter::WARN,
+        3 => LevelFilter::INFO,
+        4 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Start writing to the log file at `path`.
+///
+/// Already called on [`crate::sys::EVERYTHING_PLUGIN_PM_INIT`] by [`crate::PluginHandler`] if [`FileLog`] is set.
+pub fn init(options: &FileLog, path: PathBuf) {
+    let env_level = std::env::var(&options.env)
+        .ok()
+        .and_then(|s| LevelFilter::from_str(s.trim()).ok());
+    LEVEL_FROM_ENV.store(env_level.is_some() as u8, Ordering::Relaxed);
+    let level = env_level.unwrap_or(options.level);
+
+    match RollingFile::open(path.clone(), options.rotation, options.max_files) {
+        Ok(file) => {
+            *FILE.lock().unwrap() = Some(file);
+            LEVEL.store(level_to_u8(level), Ordering::Relaxed);
+            debug!(?path, %level, "File log");
+        }
+        Err(e) => warn!(?path, %e, "File log open error"),
+    }
+}
+
+/// Set the level of the log file, unless overridden by the environment variable.
+pub fn set_level(level: LevelFilter) {
+    if LEVEL_FROM_ENV.load(Ordering::Relaxed) == 0 && FILE.lock().unwrap().is_some() {
+        LEVEL.store(level_to_u8(level), Ordering::Relaxed);
+    }
+}
+
+pub fn level() -> LevelFilter {
+    level_from_u8(LEVEL.load(Ordering::Relaxed))
+}
+
+pub(crate) fn enabled(metadata: &Metadata<'_>) -> bool {
+    *metadata.level() <= level()
+}
+
+/// A [`MakeWriter`] writing to the log file set by [`init()`]. Outputs are discarded before that.
+pub struct FileWriter;
+
+impl<'a> MakeWriter<'a> for FileWriter {
+    type Writer = FileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        FileWriter
+    }
+}
+
+impl Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match FILE.lock().unwrap().as_mut() {
+            Some(file) => file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match FILE.lock().unwrap().as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `{name}.log` is the current file, `{name}.1.log` is the last rotated one, and so on.
+struct RollingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    max_files: usize,
+    /// `None` while rotating, or if reopening failed.
+    file: Option<File>,
+    size: u64,
+    day: u64,
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / (24 * 60 * 60))
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated_path(path: &Path, i: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}.{i}.log"))
+}
+
+impl RollingFile {
+    fn open(path: PathBuf, rotation: Rotation, max_files: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = open_append(&path)?;
+        let metadata = file.metadata()?;
+        let day = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or_else(today, |d| d.as_secs() / (24 * 60 * 60));
+        Ok(Self {
+            path,
+            rotation,
+            max_files,
+            file: Some(file),
+            size: metadata.len(),
+            day,
+        })
+    }
+
+    fn should_rotate(&self, len: usize) -> bool {
+        match self.rotation {
+            Rotation::Daily => today() != self.day,
+            Rotation::Size(max) => self.size > 0 && self.size + len as u64 > max,
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        // Close the file first, Windows can't remove an open file without it lingering
+        self.file = None;
+        if self.max_files == 0 {
+            _ = fs::remove_file(&self.path);
+        } else {
+            _ = fs::remove_file(rotated_path(&self.path, self.max_files));
+            for i in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, i);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, i + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.file = Some(open_append(&self.path)?);
+        self.size = 0;
+        self.day = today();
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.should_rotate(buf.len()) {
+            self.rotate()?;
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            file => file.insert(open_append(&self.path)?),
+        };
+        let n = file.write(buf)?;
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), File::flush)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A new log file path in a temporary directory, removed with [`remove_dir()`].
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "everything-plugin-log-{}-{name}",
+            std::process::id()
+        ));
+        _ = fs::remove_dir_all(&dir);
+        dir.join("test.log")
+    }
+
+    fn remove_dir(path: &Path) {
+        _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap_or_default()
+    }
+
+    #[test]
+    fn size_rotation() {
+        let path = temp_path("size");
+        let mut file = RollingFile::open(path.clone(), Rotation::Size(8), 2).unwrap();
+        for line in ["a1234\n", "b1234\n", "c1234\n", "d1234\n"] {
+            file.write(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert_eq!(read(&path), "d1234\n");
+        assert_eq!(read(&rotated_path(&path, 1)), "c1234\n");
+        assert_eq!(read(&rotated_path(&path, 2)), "b1234\n");
+        // Only `max_files` rotated files are kept
+        assert!(!rotated_path(&path, 3).exists());
+        remove_dir(&path);
+    }
+
+    #[test]
+    fn daily_rotation() {
+        let path = temp_path("daily");
+        let mut file = RollingFile::open(path.clone(), Rotation::Daily, 7).unwrap();
+        file.write(b"yesterday\n").unwrap();
+        file.write(b"still yesterday\n").unwrap();
+        file.day -= 1;
+        file.write(b"today\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(read(&path), "today\n");
+        assert_eq!(
+            read(&rotated_path(&path, 1)),
+            "yesterday\nstill yesterday\n"
+        );
+        assert_eq!(file.day, today());
+        remove_dir(&path);
+    }
+
+    #[test]
+    fn reopen_appends() {
+        let path = temp_path("reopen");
+        let mut file = RollingFile::open(path.clone(), Rotation::Size(16), 1).unwrap();
+        file.write(b"a1234\n").unwrap();
+        drop(file);
+
+        let mut file = RollingFile::open(path.clone(), Rotation::Size(16), 1).unwrap();
+        assert_eq!(file.size, 6);
+        file.write(b"b1234\n").unwrap();
+        file.write(b"c1234\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(read(&path), "c1234\n");
+        assert_eq!(read(&rotated_path(&path, 1)), "a1234\nb1234\n");
+        remove_dir(&path);
+    }
+
+    #[test]
+    fn no_rotated_files() {
+        let path = temp_path("none");
+        let mut file = RollingFile::open(path.clone(), Rotation::Size(8), 0).unwrap();
+        file.write(b"a1234\n").unwrap();
+        file.write(b"b1234\n").unwrap();
+        file.write(b"c1234\n").unwrap();
+        file.flush().unwrap();
+
+        // The current file is started over
+        assert_eq!(read(&path), "c1234\n");
+        assert!(!rotated_path(&path, 1).exists());
+        remove_dir(&path);
+    }
+}
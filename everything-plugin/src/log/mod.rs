@@ -0,0 +1,106 @@
+use std::{error::Error, sync::OnceLock};
+
+use tracing_subscriber::{
+    EnvFilter, Registry,
+    filter::{LevelFilter, dynamic_filter_fn},
+    fmt,
+    prelude::*,
+    reload,
+};
+
+pub use tracing::{debug, error, info, trace, warn};
+
+pub mod everything;
+pub mod file;
+
+pub use everything::EverythingLayer;
+pub use file::FileLog;
+
+/// The filter of stderr and [`EverythingLayer`].
+static RELOAD: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// A convenient function to initialize [`tracing`] with a default configuration.
+///
+/// The level is `DEBUG` and can be changed at runtime with [`set_level()`] and [`set_filter()`].
+///
+/// Events are written to stderr, and to Everything's debug console and log file with [`EverythingLayer`] once the host is available. Stderr is muted after that to avoid duplicated outputs on the `-debug` console.
+///
+/// If [`FileLog`] is set, events are also written to a log file, see [`file`].
+///
+/// Error if already inited.
+pub fn tracing_try_init() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    #[cfg(not(feature = "tracing-appender"))]
+    let stderr = anstream::stderr;
+    #[cfg(feature = "tracing-appender")]
+    let stderr = {
+        let (non_blocking, guard) = tracing_appender::non_blocking(anstream::stderr());
+        std::mem::forget(guard);
+        non_blocking
+    };
+
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(LevelFilter::DEBUG.to_string()));
+
+    tracing_subscriber::registry()
+        .with(
+            fmt::layer()
+                .with_writer(stderr)
+                // Not `filter_fn()`, whose results are cached per callsite
+                .with_filter(dynamic_filter_fn(|_, _| !everything::is_enabled()))
+                .and_then(EverythingLayer)
+                .with_filter(filter),
+        )
+        .with(
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(file::FileWriter)
+                .with_filter(dynamic_filter_fn(|metadata, _| file::enabled(metadata))),
+        )
+        .try_init()?;
+    _ = RELOAD.set(handle);
+
+    // Panics are caught by `PluginHandler::handle()`, so only log them
+    #[cfg(debug_assertions)]
+    std::panic::set_hook(Box::new(tracing_panic::panic_hook));
+
+    Ok(())
+}
+
+/// A convenient function to initialize [`tracing`] with a default configuration.
+///
+/// Panics if already inited.
+pub fn tracing_init() {
+    tracing_try_init().expect("Unable to install global subscriber")
+}
+
+/// Change the level of stderr, [`EverythingLayer`] and the log file (unless overridden by the environment variable, see [`file`]) at runtime.
+///
+/// Only works with [`tracing_try_init()`].
+pub fn set_level(level: LevelFilter) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    // Also if the filter isn't reloadable
+    file::set_level(level);
+    set_filter(&level.to_string())
+}
+
+/// Change the filter of stderr and [`EverythingLayer`] at runtime, e.g. `"info,my_plugin=trace"`.
+///
+/// See [`EnvFilter`] for the syntax of directives.
+///
+/// Only works with [`tracing_try_init()`].
+pub fn set_filter(directives: &str) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+    let filter = EnvFilter::try_new(directives)?;
+    let handle = RELOAD
+        .get()
+        .ok_or("tracing is not inited by tracing_try_init()")?;
+    handle.reload(filter)?;
+    debug!(directives, "Log filter");
+    Ok(())
+}
+
+/// The max level of stderr and [`EverythingLayer`].
+pub fn level() -> LevelFilter {
+    RELOAD
+        .get()
+        .and_then(|handle| handle.with_current(|filter| filter.max_level_hint()).ok())
+        .flatten()
+        .unwrap_or(LevelFilter::OFF)
+}
@@ -1,3 +1,5 @@
+/// See also the `#[everything_plugin]` attribute (`macros` feature), which fills metadata from Cargo package fields.
+///
 /// ```ignore
 /// plugin_main!(App, {
 ///     PluginHandler::builder()
@@ -37,9 +39,13 @@ macro_rules! plugin_main {
             msg: u32,
             data: *mut ::std::ffi::c_void,
         ) -> *mut ::std::ffi::c_void {
-            ::everything_plugin::PluginHandler::<$app_type>::handle_init_i18n(msg, data);
+            // Unwinding across the FFI boundary aborts Everything
+            ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| {
+                ::everything_plugin::PluginHandler::<$app_type>::handle_init_i18n(msg, data);
 
-            HANDLER.handle(msg, data)
+                HANDLER.handle(msg, data)
+            }))
+            .unwrap_or_else(|_| ::everything_plugin::panic::failure_value(msg))
         }
     };
 }
@@ -0,0 +1,250 @@
+//! Panic safety across the `everything_plugin_proc` FFI boundary.
+//!
+//! Unwinding out of an `extern "system"` function aborts Everything, so [`crate::PluginHandler::handle()`] catches panics, logs them and returns [`failure_value()`] instead. See also [`crate::PluginHandlerBuilder::max_panics()`].
+
+use std::{any::Any, ffi::c_void};
+
+use crate::sys;
+
+/// The name of a `EVERYTHING_PLUGIN_PM_*` message, for logging.
+pub fn message_name(msg: u32) -> &'static str {
+    match msg {
+        sys::EVERYTHING_PLUGIN_PM_INIT => "PM_INIT",
+        sys::EVERYTHING_PLUGIN_PM_GET_PLUGIN_VERSION => "PM_GET_PLUGIN_VERSION",
+        sys::EVERYTHING_PLUGIN_PM_GET_NAME => "PM_GET_NAME",
+        sys::EVERYTHING_PLUGIN_PM_GET_DESCRIPTION => "PM_GET_DESCRIPTION",
+        sys::EVERYTHING_PLUGIN_PM_GET_AUTHOR => "PM_GET_AUTHOR",
+        sys::EVERYTHING_PLUGIN_PM_GET_VERSION => "PM_GET_VERSION",
+        sys::EVERYTHING_PLUGIN_PM_GET_LINK => "PM_GET_LINK",
+        sys::EVERYTHING_PLUGIN_PM_START => "PM_START",
+        sys::EVERYTHING_PLUGIN_PM_STOP => "PM_STOP",
+        sys::EVERYTHING_PLUGIN_PM_KILL => "PM_KILL",
+        sys::EVERYTHING_PLUGIN_PM_UNINSTALL => "PM_UNINSTALL",
+        sys::EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES => "PM_ADD_OPTIONS_PAGES",
+        sys::EVERYTHING_PLUGIN_PM_LOAD_OPTIONS_PAGE => "PM_LOAD_OPTIONS_PAGE",
+        sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE => "PM_SAVE_OPTIONS_PAGE",
+        sys::EVERYTHING_PLUGIN_PM_GET_OPTIONS_PAGE_MINMAX => "PM_GET_OPTIONS_PAGE_MINMAX",
+        sys::EVERYTHING_PLUGIN_PM_SIZE_OPTIONS_PAGE => "PM_SIZE_OPTIONS_PAGE",
+        sys::EVERYTHING_PLUGIN_PM_OPTIONS_PAGE_PROC => "PM_OPTIONS_PAGE_PROC",
+        sys::EVERYTHING_PLUGIN_PM_KILL_OPTIONS_PAGE => "PM_KILL_OPTIONS_PAGE",
+        sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS => "PM_SAVE_SETTINGS",
+        _ => "PM_UNKNOWN",
+    }
+}
+
+/// Messages still handled after the plugin is disabled by [`crate::PluginHandlerBuilder::max_panics()`], so the app can be stopped and the settings saved.
+pub fn is_teardown(msg: u32) -> bool {
+    matches!(
+        msg,
+        sys::EVERYTHING_PLUGIN_PM_STOP
+            | sys::EVERYTHING_PLUGIN_PM_KILL
+            | sys::EVERYTHING_PLUGIN_PM_UNINSTALL
+            | sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS
+    )
+}
+
+/// The value returned to Everything if handling `msg` panicked.
+///
+/// - `PM_GET_PLUGIN_VERSION`: still [`sys::EVERYTHING_PLUGIN_VERSION`], so Everything can unload the plugin properly
+/// - `PM_GET_*`: null, i.e. no metadata
+/// - `PM_OPTIONS_PAGE_PROC`: 0, i.e. the message is not handled
+/// - Others: 0, i.e. failed
+pub fn failure_value(msg: u32) -> *mut c_void {
+    match msg {
+        sys::EVERYTHING_PLUGIN_PM_GET_PLUGIN_VERSION => sys::EVERYTHING_PLUGIN_VERSION as _,
+        _ => 0 as _,
+    }
+}
+
+/// The message of a panic payload, if it is a string.
+pub fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&'static str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{
+        ptr,
+        sync::{
+            Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        PluginApp, PluginHandler,
+        lifecycle::LifecycleState,
+        localization::LocalizedText,
+        sys,
+        testing::{Driver, FakeHost},
+        ui::OptionsPage,
+    };
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct Config {
+        s: String,
+    }
+
+    static STOPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct App {
+        config: Config,
+    }
+
+    impl PluginApp for App {
+        type Config = Config;
+
+        fn new(config: Option<Self::Config>) -> Self {
+            Self {
+                config: config.unwrap_or_default(),
+            }
+        }
+
+        fn stop(&self) {
+            STOPS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn config(&self) -> &Self::Config {
+            &self.config
+        }
+
+        fn into_config(self) -> Self::Config {
+            self.config
+        }
+    }
+
+    #[test]
+    fn teardown_after_disabled() {
+        let host = FakeHost::new();
+        host.set_setting("_", r#"{"s":"Hi"}"#);
+        let handler = PluginHandler::<App>::builder()
+            .name("Test Plugin")
+            .max_panics(1)
+            .options_pages(vec![
+                OptionsPage::builder()
+                    .name(LocalizedText::from_fn(|| panic!("options page name")))
+                    .load(|_| unreachable!())
+                    .build(),
+            ])
+            .build();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        assert!(driver.add_options_pages().is_null());
+        assert!(handler.is_disabled());
+        // Other messages are ignored
+        assert!(
+            driver
+                .send(sys::EVERYTHING_PLUGIN_PM_GET_NAME, ptr::null_mut())
+                .is_null()
+        );
+
+        host.set_setting("_", "");
+        let stops = STOPS.load(Ordering::SeqCst);
+        driver.shutdown();
+        assert_eq!(STOPS.load(Ordering::SeqCst), stops + 1);
+        assert_eq!(handler.state(), LifecycleState::Killed);
+        assert_eq!(host.setting("_").as_deref(), Some(r#"{"s":"Hi"}"#));
+    }
+
+    /// The [`PluginApp`] method to panic in.
+    static PANIC_IN: Mutex<Option<&str>> = Mutex::new(None);
+
+    fn panic_in(f: &str) {
+        if *PANIC_IN.lock().unwrap() == Some(f) {
+            panic!("{f} panicked");
+        }
+    }
+
+    struct PanicApp {
+        config: Config,
+    }
+
+    impl PluginApp for PanicApp {
+        type Config = Config;
+
+        fn new(config: Option<Self::Config>) -> Self {
+            panic_in("new");
+            Self {
+                config: config.unwrap_or_default(),
+            }
+        }
+
+        fn start(&self) {
+            panic_in("start");
+        }
+
+        fn stop(&self) {
+            panic_in("stop");
+        }
+
+        fn config(&self) -> &Self::Config {
+            &self.config
+        }
+
+        fn into_config(self) -> Self::Config {
+            self.config
+        }
+    }
+
+    #[test]
+    fn app_panics() {
+        let host = FakeHost::new();
+        let handler = PluginHandler::<PanicApp>::builder()
+            .name("Test Plugin")
+            .max_panics(2)
+            .build();
+
+        let driver = Driver::new(&handler, &host);
+        driver.init();
+        *PANIC_IN.lock().unwrap() = Some("new");
+        assert!(driver.start().is_null());
+        assert_eq!(handler.state(), LifecycleState::Inited);
+        assert!(!handler.is_disabled());
+
+        *PANIC_IN.lock().unwrap() = Some("start");
+        assert!(driver.start().is_null());
+        assert_eq!(handler.state(), LifecycleState::Inited);
+        assert!(handler.is_disabled());
+
+        // Still answered with the failure value
+        assert_eq!(
+            driver.send(
+                sys::EVERYTHING_PLUGIN_PM_GET_PLUGIN_VERSION,
+                ptr::null_mut()
+            ),
+            sys::EVERYTHING_PLUGIN_VERSION as _
+        );
+        assert!(driver.start().is_null());
+        assert_eq!(handler.state(), LifecycleState::Inited);
+        // But teardown is still handled
+        assert!(!driver.kill().is_null());
+        assert_eq!(handler.state(), LifecycleState::Killed);
+        drop(driver);
+
+        let handler = PluginHandler::<PanicApp>::builder()
+            .name("Test Plugin")
+            .max_panics(2)
+            .build();
+        let driver = Driver::new(&handler, &host);
+        *PANIC_IN.lock().unwrap() = Some("stop");
+        driver.startup();
+        assert_eq!(handler.state(), LifecycleState::Started);
+        assert!(driver.stop().is_null());
+        assert_eq!(handler.state(), LifecycleState::Started);
+        assert!(!handler.is_disabled());
+
+        // Not stopped twice
+        assert!(!driver.kill().is_null());
+        assert_eq!(handler.state(), LifecycleState::Killed);
+        *PANIC_IN.lock().unwrap() = None;
+    }
+}
@@ -0,0 +1,135 @@
+//! ## Everything plugin SDK property API
+//! Properties are the columns of Everything, e.g. name, path and size.
+//!
+//! Registering plugin-provided properties is not part of the SDK and not supported.
+
+use std::{
+    ffi::{CString, NulError},
+    mem::MaybeUninit,
+};
+
+use crate::{PluginHost, sys};
+
+/// Built-in property types.
+///
+/// [`sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_*`](sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_NAME)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum PropertyType {
+    Name = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_NAME,
+    Path = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_PATH,
+    Size = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_SIZE,
+    Extension = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_EXTENSION,
+    Type = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_TYPE,
+    DateModified = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_DATE_MODIFIED,
+    DateCreated = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_DATE_CREATED,
+    DateAccessed = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_DATE_ACCESSED,
+    Attributes = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_ATTRIBUTES,
+    DateRecentlyChanged = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_DATE_RECENTLY_CHANGED,
+    RunCount = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_RUN_COUNT,
+    DateRun = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_DATE_RUN,
+    FileListFilename = sys::EVERYTHING_PLUGIN_PROPERTY_TYPE_FILE_LIST_FILENAME,
+}
+
+/// A property owned by Everything.
+///
+/// Properties live as long as Everything and don't need to be released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Property(*mut sys::everything_plugin_property_t);
+
+impl Property {
+    pub fn from_raw(property: *mut sys::everything_plugin_property_t) -> Self {
+        Self(property)
+    }
+
+    pub fn as_raw(self) -> *mut sys::everything_plugin_property_t {
+        self.0
+    }
+}
+
+impl PropertyType {
+    /// The type of the values of this property.
+    pub fn value_type(self) -> PropertyValueType {
+        match self {
+            Self::Size => PropertyValueType::Size,
+            Self::DateModified
+            | Self::DateCreated
+            | Self::DateAccessed
+            | Self::DateRecentlyChanged
+            | Self::DateRun => PropertyValueType::FileTime,
+            Self::Attributes | Self::RunCount => PropertyValueType::Dword,
+            Self::Name | Self::Path | Self::Extension | Self::Type | Self::FileListFilename => {
+                PropertyValueType::String
+            }
+        }
+    }
+}
+
+/// The type of values of a property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PropertyValueType {
+    String,
+    /// Size in bytes.
+    Size,
+    /// [`FILETIME`](https://learn.microsoft.com/en-us/windows/win32/api/minwinbase/ns-minwinbase-filetime), 100-nanosecond intervals since January 1, 1601 (UTC).
+    FileTime,
+    Dword,
+}
+
+/// A property value in typed form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PropertyValue {
+    /// The value is unknown or not available, e.g. the size of a folder that is not indexed.
+    None,
+    String(String),
+    Size(u64),
+    FileTime(u64),
+    Dword(u32),
+}
+
+impl PluginHost {
+    /// Get a built-in property.
+    ///
+    /// The property is owned by Everything and doesn't need to be released.
+    pub fn property_get_builtin_type(
+        &self,
+        ty: PropertyType,
+    ) -> *mut sys::everything_plugin_property_t {
+        let property_get_builtin_type: unsafe extern "system" fn(
+            ty: sys::DWORD,
+        ) -> *mut sys::everything_plugin_property_t =
+            unsafe { self.require("property_get_builtin_type") };
+        unsafe { property_get_builtin_type(ty as _) }
+    }
+
+    pub fn property_get_builtin(&self, ty: PropertyType) -> Property {
+        Property(self.property_get_builtin_type(ty))
+    }
+
+    /// Find a property by its canonical name, e.g. `"Name"` or `"Size"`.
+    ///
+    /// `Ok(None)` if not found.
+    pub fn property_find(&self, name: &str) -> Result<Option<Property>, NulError> {
+        let property_find: unsafe extern "system" fn(
+            name: *const sys::everything_plugin_utf8_t,
+        )
+            -> *mut sys::everything_plugin_property_t = unsafe { self.require("property_find") };
+        let name = CString::new(name)?;
+        let property = unsafe { property_find(name.as_ptr() as _) };
+        Ok((!property.is_null()).then_some(Property(property)))
+    }
+
+    pub fn property_get_canonical_name(&self, property: Property) -> String {
+        let property_get_canonical_name: unsafe extern "system" fn(
+            property: *mut sys::everything_plugin_property_t,
+            cbuf: *mut sys::everything_plugin_utf8_buf_t,
+        ) = unsafe { self.require("property_get_canonical_name") };
+
+        let mut cbuf = MaybeUninit::uninit();
+        self.utf8_buf_init(cbuf.as_mut_ptr());
+
+        unsafe { property_get_canonical_name(property.as_raw(), cbuf.as_mut_ptr()) };
+
+        self.utf8_buf_into_string(cbuf.as_mut_ptr())
+    }
+}
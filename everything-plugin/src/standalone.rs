@@ -0,0 +1,302 @@
+//! Run a [`PluginApp`] outside Everything, to develop and demo plugins without restarting Everything.
+//!
+//! [`run_standalone()`]:
+//! 1. Loads config from a file, with [`crate::data::format`] and [`crate::data::migrate`]
+//! 2. Starts the app with [`PluginHandler::init_start_with_config()`]
+//! 3. Opens each [`crate::ui::OptionsPage`] as a top-level window with Save/Cancel, all on the current thread
+//!    - Save applies the config (like Apply in Everything) and writes it to the file, unless rejected by [`PluginApp::validate()`]
+//!    - Cancel closes the window, discarding unsaved changes
+//! 4. Stops the app with [`PluginHandler::stop_kill()`] after all windows are closed
+//!
+//! Host APIs are unavailable, i.e. [`PluginHandler::get_host()`] returns `None`.
+//!
+//! ## Example
+//! ```ignore
+//! plugin_main!(App, {
+//!     PluginHandler::builder()
+//!         // ...
+//!         .build()
+//! });
+//!
+//! pub fn main() {
+//!     everything_plugin::standalone::run_standalone(&*HANDLER, "config.json");
+//! }
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use tracing::{debug, error, warn};
+use windows_sys::Win32::{
+    Foundation::{HWND, RECT},
+    UI::WindowsAndMessaging::GetClientRect,
+};
+use winio::prelude::*;
+
+use crate::{PluginApp, PluginHandler, sys};
+
+/// See [module-level documentation](self).
+///
+/// Blocks until all options page windows are closed.
+pub fn run_standalone<A: PluginApp>(handler: &'static PluginHandler<A>, path: impl AsRef<Path>) {
+    let path = path.as_ref().to_path_buf();
+    let config = match fs::read_to_string(&path) {
+        Ok(config) => handler.load_config_str(&config),
+        Err(e) => {
+            debug!(?path, %e, "Standalone config read error");
+            None
+        }
+    };
+    match config {
+        Some(config) => handler.init_start_with_config(config),
+        None => handler.init_start(),
+    }
+
+    if handler.options_pages.is_empty() {
+        warn!("No options pages to open");
+    } else {
+        App::new("").run::<Standalone<A>>(StandaloneInit { handler, path });
+    }
+
+    handler.stop_kill();
+}
+
+struct StandaloneInit<A: PluginApp> {
+    handler: &'static PluginHandler<A>,
+    path: PathBuf,
+}
+
+/// The windows of all options pages. The handler is only called from this thread, as in Everything.
+struct Standalone<A: PluginApp> {
+    /// `None` if closed.
+    frames: Vec<Option<Child<PageFrame<A>>>>,
+}
+
+#[derive(Debug)]
+enum StandaloneMessage {
+    /// A message of the frame to be updated.
+    Frame(usize),
+    Close(usize),
+}
+
+impl<A: PluginApp> Component for Standalone<A> {
+    type Event = ();
+    type Init<'a> = StandaloneInit<A>;
+    type Message = StandaloneMessage;
+
+    fn init(init: Self::Init<'_>, _sender: &ComponentSender<Self>) -> Self {
+        let frames = (0..init.handler.options_pages.len())
+            .map(|index| {
+                Some(Child::<PageFrame<A>>::init(PageFrameInit {
+                    handler: init.handler,
+                    index,
+                    path: init.path.clone(),
+                }))
+            })
+            .collect();
+        Self { frames }
+    }
+
+    async fn start(&mut self, sender: &ComponentSender<Self>) -> ! {
+        let frames = self
+            .frames
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, frame)| Some((index, frame.as_mut()?)))
+            .map(|(index, frame)| {
+                frame.start(
+                    sender,
+                    move |()| Some(StandaloneMessage::Close(index)),
+                    move || StandaloneMessage::Frame(index),
+                )
+            });
+        futures_util::future::join_all(frames).await;
+        // All frames are closed
+        std::future::pending().await
+    }
+
+    async fn update(&mut self, message: Self::Message, sender: &ComponentSender<Self>) -> bool {
+        match message {
+            StandaloneMessage::Frame(index) => match &mut self.frames[index] {
+                Some(frame) => frame.update().await,
+                None => false,
+            },
+            StandaloneMessage::Close(index) => {
+                // Destroy the window
+                self.frames[index] = None;
+                if self.frames.iter().all(Option::is_none) {
+                    sender.output(());
+                }
+                false
+            }
+        }
+    }
+
+    fn render(&mut self, _sender: &ComponentSender<Self>) {
+        for frame in self.frames.iter_mut().flatten() {
+            frame.render();
+        }
+    }
+}
+
+struct PageFrameInit<A: PluginApp> {
+    handler: &'static PluginHandler<A>,
+    index: usize,
+    path: PathBuf,
+}
+
+/// A top-level window hosting an options page, like the options window of Everything.
+struct PageFrame<A: PluginApp> {
+    window: Child<Window>,
+    save: Child<Button>,
+    cancel: Child<Button>,
+    handler: &'static PluginHandler<A>,
+    index: usize,
+    path: PathBuf,
+}
+
+#[derive(Debug)]
+enum PageFrameMessage {
+    Noop,
+    Close,
+    Redraw,
+    Save,
+}
+
+impl<A: PluginApp> PageFrame<A> {
+    fn hwnd(&self) -> HWND {
+        self.window.as_raw_window().as_win32()
+    }
+
+    fn load(&self) {
+        let mut data = sys::everything_plugin_load_options_page_s {
+            user_data: self.index as _,
+            page_hwnd: self.hwnd() as _,
+            tooltip_hwnd: ptr::null_mut(),
+        };
+        self.handler.load_options_page(&raw mut data as _);
+    }
+
+    /// `Err` with the message to show if not saved.
+    fn save(&self) -> Result<(), String> {
+        let mut data = sys::everything_plugin_save_options_page_s {
+            user_data: self.index as _,
+            page_hwnd: self.hwnd() as _,
+            enable_apply: 0,
+        };
+        let saved = !self.handler.save_options_page(&raw mut data as _).is_null();
+        // Don't wait for other windows
+        self.handler.config_flush();
+        if !saved {
+            // E.g. rejected by `PluginApp::validate()`, the errors are shown by the page
+            warn!(index = self.index, "Standalone options page not saved");
+            return Err("The settings are invalid.".into());
+        }
+        self.handler.export_config(&self.path).map_err(|e| {
+            error!(path = ?self.path, %e, "Standalone config write error");
+            format!("{}\n{e}", self.path.display())
+        })
+    }
+
+    fn kill(&self) {
+        self.handler.kill_options_page(self.index as _);
+    }
+}
+
+impl<A: PluginApp> Component for PageFrame<A> {
+    type Event = ();
+    type Init<'a> = PageFrameInit<A>;
+    type Message = PageFrameMessage;
+
+    fn init(init: Self::Init<'_>, _sender: &ComponentSender<Self>) -> Self {
+        let mut window = Child::<Window>::init(None::<BorrowedWindow>);
+        window.set_text(init.handler.options_pages[init.index].name());
+        window.set_size(Size::new(640.0, 480.0));
+
+        let mut save = Child::<Button>::init(&window);
+        save.set_text("Save");
+        let mut cancel = Child::<Button>::init(&window);
+        cancel.set_text("Cancel");
+
+        window.show();
+
+        let frame = Self {
+            window,
+            save,
+            cancel,
+            handler: init.handler,
+            index: init.index,
+            path: init.path,
+        };
+        frame.load();
+        frame
+    }
+
+    async fn start(&mut self, sender: &ComponentSender<Self>) -> ! {
+        start! {
+            sender, default: PageFrameMessage::Noop,
+            self.window => {
+                WindowEvent::Close => PageFrameMessage::Close,
+                WindowEvent::Resize => PageFrameMessage::Redraw,
+            },
+            self.save => {
+                ButtonEvent::Click => PageFrameMessage::Save,
+            },
+            self.cancel => {
+                ButtonEvent::Click => PageFrameMessage::Close,
+            }
+        }
+    }
+
+    async fn update(&mut self, message: Self::Message, sender: &ComponentSender<Self>) -> bool {
+        futures_util::join!(self.window.update());
+        match message {
+            PageFrameMessage::Noop => false,
+            PageFrameMessage::Close => {
+                self.kill();
+                sender.output(());
+                false
+            }
+            PageFrameMessage::Redraw => true,
+            PageFrameMessage::Save => {
+                if let Err(message) = self.save() {
+                    MessageBox::new()
+                        .title("Failed to save settings")
+                        .message(message)
+                        .buttons(MessageBoxButton::Ok)
+                        .show(&self.window)
+                        .await;
+                }
+                false
+            }
+        }
+    }
+
+    fn render(&mut self, _sender: &ComponentSender<Self>) {
+        self.window.render();
+
+        let csize = self.window.client_size();
+
+        let m = Margin::new(5., 5., 5., 5.);
+        let mut buttons = layout! {
+            StackPanel::new(Orient::Horizontal),
+            self.save => { margin: m },
+            self.cancel => { margin: m },
+        };
+        let mut grid = layout! {
+            Grid::from_str("1*", "1*,auto").unwrap(),
+            buttons => { column: 0, row: 1, halign: HAlign::Right },
+        };
+        grid.set_size(csize);
+
+        // The page is sized in physical pixels, as by `WM_SIZE` in Everything
+        let mut rect: RECT = unsafe { std::mem::zeroed() };
+        unsafe { GetClientRect(self.hwnd(), &mut rect) };
+        let top = self.save.loc().y - m.top;
+        let height = (rect.bottom as f64 * top / csize.height) as i32;
+        self.handler.options_pages[self.index].resize((rect.right, height));
+    }
+}
@@ -233,6 +233,7 @@ pub struct HWND__ {
     pub unused: ::std::os::raw::c_int,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of HWND__"][::std::mem::size_of::<HWND__>() - 4usize];
     ["Alignment of HWND__"][::std::mem::align_of::<HWND__>() - 4usize];
@@ -260,6 +261,7 @@ pub struct _OVERLAPPED__bindgen_ty_1__bindgen_ty_1 {
     pub OffsetHigh: DWORD,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of _OVERLAPPED__bindgen_ty_1__bindgen_ty_1"]
         [::std::mem::size_of::<_OVERLAPPED__bindgen_ty_1__bindgen_ty_1>() - 8usize];
@@ -271,6 +273,7 @@ const _: () = {
         [::std::mem::offset_of!(_OVERLAPPED__bindgen_ty_1__bindgen_ty_1, OffsetHigh) - 4usize];
 };
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of _OVERLAPPED__bindgen_ty_1"]
         [::std::mem::size_of::<_OVERLAPPED__bindgen_ty_1>() - 8usize];
@@ -280,6 +283,7 @@ const _: () = {
         [::std::mem::offset_of!(_OVERLAPPED__bindgen_ty_1, Pointer) - 0usize];
 };
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of _OVERLAPPED"][::std::mem::size_of::<_OVERLAPPED>() - 32usize];
     ["Alignment of _OVERLAPPED"][::std::mem::align_of::<_OVERLAPPED>() - 8usize];
@@ -391,6 +395,7 @@ pub struct everything_plugin_utf8_buf_s {
     pub stack: [everything_plugin_utf8_t; 260usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_utf8_buf_s"]
         [::std::mem::size_of::<everything_plugin_utf8_buf_s>() - 288usize];
@@ -416,6 +421,7 @@ pub struct everything_plugin_fileinfo_fd_s {
     pub attributes: DWORD,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_fileinfo_fd_s"]
         [::std::mem::size_of::<everything_plugin_fileinfo_fd_s>() - 40usize];
@@ -444,6 +450,7 @@ pub struct everything_plugin_load_options_page_s {
     pub tooltip_hwnd: HWND,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_load_options_page_s"]
         [::std::mem::size_of::<everything_plugin_load_options_page_s>() - 24usize];
@@ -470,6 +477,7 @@ pub struct everything_plugin_options_page_proc_s {
     pub handled: ::std::os::raw::c_int,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_options_page_proc_s"]
         [::std::mem::size_of::<everything_plugin_options_page_proc_s>() - 64usize];
@@ -500,6 +508,7 @@ pub struct http_server_everything_plugin_proc_s {
     pub proc_address_ptr: *mut *mut ::std::os::raw::c_void,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of http_server_everything_plugin_proc_s"]
         [::std::mem::size_of::<http_server_everything_plugin_proc_s>() - 16usize];
@@ -521,6 +530,7 @@ pub struct everything_plugin_save_options_page_s {
     pub enable_apply: ::std::os::raw::c_int,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_save_options_page_s"]
         [::std::mem::size_of::<everything_plugin_save_options_page_s>() - 24usize];
@@ -546,6 +556,7 @@ pub struct everything_plugin_get_options_page_minmax_s {
     pub high: ::std::os::raw::c_int,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_get_options_page_minmax_s"]
         [::std::mem::size_of::<everything_plugin_get_options_page_minmax_s>() - 24usize];
@@ -570,6 +581,7 @@ pub struct everything_plugin_size_options_page_s {
     pub page_hwnd: HWND,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_size_options_page_s"]
         [::std::mem::size_of::<everything_plugin_size_options_page_s>() - 16usize];
@@ -601,6 +613,7 @@ pub struct everything_plugin_os_winsock_addrinfo {
     pub ai_next: *mut everything_plugin_os_winsock_addrinfo,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_winsock_addrinfo"]
         [::std::mem::size_of::<everything_plugin_os_winsock_addrinfo>() - 48usize];
@@ -635,6 +648,7 @@ pub struct everything_plugin_os_winsock_sockaddr_storage {
     pub __ss_pad2: [::std::os::raw::c_char; 112usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_winsock_sockaddr_storage"]
         [::std::mem::size_of::<everything_plugin_os_winsock_sockaddr_storage>() - 128usize];
@@ -665,6 +679,7 @@ pub struct EVERYTHING_PLUGIN_OS_WINSOCK_WSADATA {
     pub szSystemStatus: [::std::os::raw::c_char; 129usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of EVERYTHING_PLUGIN_OS_WINSOCK_WSADATA"]
         [::std::mem::size_of::<EVERYTHING_PLUGIN_OS_WINSOCK_WSADATA>() - 408usize];
@@ -694,6 +709,7 @@ pub struct everything_plugin_utf8_string_s {
     pub len: usize,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_utf8_string_s"]
         [::std::mem::size_of::<everything_plugin_utf8_string_s>() - 16usize];
@@ -714,6 +730,7 @@ pub struct everything_plugin_utf8_const_string_s {
     pub len: usize,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_utf8_const_string_s"]
         [::std::mem::size_of::<everything_plugin_utf8_const_string_s>() - 16usize];
@@ -732,6 +749,7 @@ pub struct everything_plugin_utf8_basic_string_s {
     pub len: usize,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_utf8_basic_string_s"]
         [::std::mem::size_of::<everything_plugin_utf8_basic_string_s>() - 8usize];
@@ -749,6 +767,7 @@ pub struct everything_plugin_os_winsock_sockaddr {
     pub sa_data: [::std::os::raw::c_char; 14usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_winsock_sockaddr"]
         [::std::mem::size_of::<everything_plugin_os_winsock_sockaddr>() - 16usize];
@@ -771,6 +790,7 @@ pub union everything_plugin_os_winsock_in6_addr__bindgen_ty_1 {
     pub Word: [::std::os::raw::c_ushort; 8usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_winsock_in6_addr__bindgen_ty_1"]
         [::std::mem::size_of::<everything_plugin_os_winsock_in6_addr__bindgen_ty_1>() - 16usize];
@@ -786,6 +806,7 @@ const _: () = {
     ) - 0usize];
 };
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_winsock_in6_addr"]
         [::std::mem::size_of::<everything_plugin_os_winsock_in6_addr>() - 16usize];
@@ -808,6 +829,7 @@ pub struct everything_plugin_os_winsock_sockaddr_in6 {
     pub sin6_scope_id: ::std::os::raw::c_ulong,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_winsock_sockaddr_in6"]
         [::std::mem::size_of::<everything_plugin_os_winsock_sockaddr_in6>() - 28usize];
@@ -847,6 +869,7 @@ pub struct everything_plugin_os_in_addr__bindgen_ty_1__bindgen_ty_1 {
     pub s_b4: ::std::os::raw::c_uchar,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_in_addr__bindgen_ty_1__bindgen_ty_1"][::std::mem::size_of::<
         everything_plugin_os_in_addr__bindgen_ty_1__bindgen_ty_1,
@@ -878,6 +901,7 @@ pub struct everything_plugin_os_in_addr__bindgen_ty_1__bindgen_ty_2 {
     pub s_w2: ::std::os::raw::c_ushort,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_in_addr__bindgen_ty_1__bindgen_ty_2"][::std::mem::size_of::<
         everything_plugin_os_in_addr__bindgen_ty_1__bindgen_ty_2,
@@ -895,6 +919,7 @@ const _: () = {
     ) - 2usize];
 };
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_in_addr__bindgen_ty_1"]
         [::std::mem::size_of::<everything_plugin_os_in_addr__bindgen_ty_1>() - 4usize];
@@ -908,6 +933,7 @@ const _: () = {
         [::std::mem::offset_of!(everything_plugin_os_in_addr__bindgen_ty_1, S_addr) - 0usize];
 };
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_in_addr"]
         [::std::mem::size_of::<everything_plugin_os_in_addr>() - 4usize];
@@ -925,6 +951,7 @@ pub struct everything_plugin_os_sockaddr_in {
     pub sin_zero: [::std::os::raw::c_char; 8usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_os_sockaddr_in"]
         [::std::mem::size_of::<everything_plugin_os_sockaddr_in>() - 16usize];
@@ -948,6 +975,7 @@ pub struct everything_plugin_ansi_buf_s {
     pub stack: [::std::os::raw::c_char; 260usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_ansi_buf_s"]
         [::std::mem::size_of::<everything_plugin_ansi_buf_s>() - 288usize];
@@ -970,6 +998,7 @@ pub struct everything_plugin_interlocked_s {
     pub unaligned_b: usize,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of everything_plugin_interlocked_s"]
         [::std::mem::size_of::<everything_plugin_interlocked_s>() - 16usize];
@@ -989,6 +1018,7 @@ pub struct EVERYTHING_PLUGIN_OS_WINSOCK_WSABUF {
     pub buf: *mut ::std::os::raw::c_char,
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of EVERYTHING_PLUGIN_OS_WINSOCK_WSABUF"]
         [::std::mem::size_of::<EVERYTHING_PLUGIN_OS_WINSOCK_WSABUF>() - 16usize];
@@ -1006,6 +1036,7 @@ pub struct EVERYTHING_PLUGIN_OS_WINSOCK_WSANETWORKEVENTS {
     pub iErrorCode: [::std::os::raw::c_int; 10usize],
 }
 #[allow(clippy::unnecessary_operation, clippy::identity_op)]
+#[cfg(windows)]
 const _: () = {
     ["Size of EVERYTHING_PLUGIN_OS_WINSOCK_WSANETWORKEVENTS"]
         [::std::mem::size_of::<EVERYTHING_PLUGIN_OS_WINSOCK_WSANETWORKEVENTS>() - 44usize];
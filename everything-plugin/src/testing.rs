@@ -0,0 +1,1026 @@
+//! A headless fake of Everything, for testing plugins with `cargo test` without Everything.
+//!
+//! - [`FakeHost`] backs [`sys::everything_plugin_get_proc_address_t`] with Rust closures and records every host call.
+//! - [`Driver`] sends realistic `PM_*` sequences to a [`PluginHandler`].
+//!
+//! Host APIs:
+//! - `utf8_buf_init`, `utf8_buf_kill`, `utf8_buf_copy_utf8_string`
+//! - `config_get_int_value`, `config_set_int_value`
+//! - `plugin_get_setting_string`, `plugin_set_setting_string`: an in-memory ini section
+//! - `os_get_app_data_path_cat_filename`, `os_get_local_app_data_path_cat_filename`: a temporary directory, removed on drop
+//! - `ui_options_add_plugin_page`
+//!
+//! Other APIs are unavailable, i.e. [`PluginHost::get()`] returns `None`. Options pages need a real window, use [`options_page()`] for a headless one instead.
+//!
+//! Win32 APIs called by the crate itself are also replaced while a [`Driver`] exists, so tests also run on non-Windows targets if the `winio` feature is disabled:
+//! - The instance name: `None`, see [`FakeHost::on_instance_name()`]
+//! - The language name: `en-US`, see [`FakeHost::on_language_name()`]
+//! - The plugin DLL name: `TestPlugin.dll`, see [`FakeHost::on_plugin_dll_name()`]
+//! - Message boxes: recorded, see [`FakeHost::message_boxes()`]
+//!
+//! Only one [`Driver`] can exist at a time, as the host APIs are plain function pointers. Other drivers wait for it to be dropped, so tests are serialized.
+//!
+//! ## Example
+//! Since [`crate::plugin_main!`]'s `HANDLER` is private, tests should be put in the plugin crate:
+//! ```ignore
+//! #[cfg(test)]
+//! mod tests {
+//!     use everything_plugin::testing::{Driver, FakeHost};
+//!
+//!     use super::*;
+//!
+//!     #[test]
+//!     fn save_settings() {
+//!         let host = FakeHost::new();
+//!         host.set_setting("_", r#"{"s":"Hi"}"#);
+//!
+//!         let driver = Driver::new(&HANDLER, &host);
+//!         driver.startup();
+//!         assert_eq!(HANDLER.with_config(|config| config.s.clone()), "Hi");
+//!         driver.shutdown();
+//!
+//!         assert!(host.calls().iter().any(|call| call.name == "plugin_set_setting_string"));
+//!     }
+//! }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    ffi::{CStr, CString, c_void},
+    fs,
+    path::{Path, PathBuf},
+    ptr::{self, NonNull},
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use tracing::debug;
+
+use crate::{
+    PluginApp, PluginHandler, PluginHost,
+    panic::message_name,
+    sys,
+    ui::{OptionsPageLoadArgs, OptionsPageMessage, PageHandle},
+};
+
+/// A recorded host API call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostCall {
+    pub name: &'static str,
+    pub args: Vec<String>,
+}
+
+#[derive(Default)]
+struct State {
+    settings: BTreeMap<String, String>,
+    config: BTreeMap<String, i32>,
+    options_pages: Vec<String>,
+    message_boxes: Vec<(String, String)>,
+}
+
+type GetSettingHook = Box<dyn FnMut(&str) -> Option<String> + Send>;
+type SetSettingHook = Box<dyn FnMut(&str, &str) + Send>;
+type GetIntHook = Box<dyn FnMut(&str) -> i32 + Send>;
+type SetIntHook = Box<dyn FnMut(&str, i32) -> i32 + Send>;
+type PathHook = Box<dyn FnMut(&str) -> PathBuf + Send>;
+type AddPageHook = Box<dyn FnMut(usize, &str) + Send>;
+type NameHook = Box<dyn FnMut() -> Option<String> + Send>;
+type LanguageHook = Box<dyn FnMut(Option<u16>) -> String + Send>;
+type MessageBoxHook = Box<dyn FnMut(&str, &str) + Send>;
+
+struct Hooks {
+    plugin_get_setting_string: GetSettingHook,
+    plugin_set_setting_string: SetSettingHook,
+    config_get_int_value: GetIntHook,
+    config_set_int_value: SetIntHook,
+    os_get_app_data_path_cat_filename: PathHook,
+    os_get_local_app_data_path_cat_filename: PathHook,
+    ui_options_add_plugin_page: AddPageHook,
+
+    // Win32
+    instance_name: NameHook,
+    language_name: LanguageHook,
+    plugin_dll_name: NameHook,
+    message_box: MessageBoxHook,
+}
+
+struct Inner {
+    hooks: Mutex<Hooks>,
+    calls: Mutex<Vec<HostCall>>,
+    /// Keep strings returned to the plugin alive.
+    strings: Mutex<Vec<CString>>,
+}
+
+/// See [module-level documentation](self).
+pub struct FakeHost {
+    inner: Arc<Inner>,
+    state: Arc<Mutex<State>>,
+    dir: PathBuf,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Don't cascade panics of other tests
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl FakeHost {
+    /// Settings and Everything config are empty. App data paths are in a new temporary directory.
+    pub fn new() -> Self {
+        static ID: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "everything-plugin-{}-{}",
+            std::process::id(),
+            ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let app_data = dir.join("Roaming");
+        let local_app_data = dir.join("Local");
+
+        let state = Arc::new(Mutex::new(State::default()));
+        let hooks = Hooks {
+            plugin_get_setting_string: Box::new({
+                let state = state.clone();
+                move |name| lock(&state).settings.get(name).cloned()
+            }),
+            plugin_set_setting_string: Box::new({
+                let state = state.clone();
+                move |name, value| {
+                    lock(&state).settings.insert(name.into(), value.into());
+                }
+            }),
+            config_get_int_value: Box::new({
+                let state = state.clone();
+                move |name| lock(&state).config.get(name).copied().unwrap_or_default()
+            }),
+            config_set_int_value: Box::new({
+                let state = state.clone();
+                move |name, value| {
+                    let old = lock(&state).config.insert(name.into(), value);
+                    (old != Some(value)) as i32
+                }
+            }),
+            os_get_app_data_path_cat_filename: Box::new(move |filename| app_data.join(filename)),
+            os_get_local_app_data_path_cat_filename: Box::new(move |filename| {
+                local_app_data.join(filename)
+            }),
+            ui_options_add_plugin_page: Box::new({
+                let state = state.clone();
+                move |_user_data, name| lock(&state).options_pages.push(name.into())
+            }),
+            instance_name: Box::new(|| None),
+            language_name: Box::new(|_language| "en-US".into()),
+            plugin_dll_name: Box::new(|| Some("TestPlugin.dll".into())),
+            message_box: Box::new({
+                let state = state.clone();
+                move |caption, text| {
+                    lock(&state)
+                        .message_boxes
+                        .push((caption.into(), text.into()))
+                }
+            }),
+        };
+
+        Self {
+            inner: Arc::new(Inner {
+                hooks: Mutex::new(hooks),
+                calls: Default::default(),
+                strings: Default::default(),
+            }),
+            state,
+            dir,
+        }
+    }
+
+    /// The value written by `plugin_set_setting_string` or [`Self::set_setting()`].
+    pub fn setting(&self, name: &str) -> Option<String> {
+        lock(&self.state).settings.get(name).cloned()
+    }
+
+    /// Set a value to be read by `plugin_get_setting_string`.
+    pub fn set_setting(&self, name: &str, value: &str) -> &Self {
+        lock(&self.state).settings.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn settings(&self) -> BTreeMap<String, String> {
+        lock(&self.state).settings.clone()
+    }
+
+    /// An Everything config value, e.g. `language`.
+    pub fn config_int(&self, name: &str) -> i32 {
+        lock(&self.state)
+            .config
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_config_int(&self, name: &str, value: i32) -> &Self {
+        lock(&self.state).config.insert(name.into(), value);
+        self
+    }
+
+    /// The names added by `ui_options_add_plugin_page`.
+    pub fn options_pages(&self) -> Vec<String> {
+        lock(&self.state).options_pages.clone()
+    }
+
+    /// `(caption, text)` of the message boxes shown to the user, e.g. config errors.
+    pub fn message_boxes(&self) -> Vec<(String, String)> {
+        lock(&self.state).message_boxes.clone()
+    }
+
+    /// The temporary directory containing `Roaming` (app data) and `Local` (local app data).
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// All host API calls since created or [`Self::clear_calls()`], in order.
+    pub fn calls(&self) -> Vec<HostCall> {
+        lock(&self.inner.calls).clone()
+    }
+
+    pub fn clear_calls(&self) {
+        lock(&self.inner.calls).clear();
+    }
+
+    /// Replace the default in-memory ini section. [`Self::setting()`] will not see the values anymore.
+    pub fn on_plugin_get_setting_string(
+        &self,
+        f: impl FnMut(&str) -> Option<String> + Send + 'static,
+    ) -> &Self {
+        lock(&self.inner.hooks).plugin_get_setting_string = Box::new(f);
+        self
+    }
+
+    pub fn on_plugin_set_setting_string(
+        &self,
+        f: impl FnMut(&str, &str) + Send + 'static,
+    ) -> &Self {
+        lock(&self.inner.hooks).plugin_set_setting_string = Box::new(f);
+        self
+    }
+
+    pub fn on_config_get_int_value(&self, f: impl FnMut(&str) -> i32 + Send + 'static) -> &Self {
+        lock(&self.inner.hooks).config_get_int_value = Box::new(f);
+        self
+    }
+
+    pub fn on_config_set_int_value(
+        &self,
+        f: impl FnMut(&str, i32) -> i32 + Send + 'static,
+    ) -> &Self {
+        lock(&self.inner.hooks).config_set_int_value = Box::new(f);
+        self
+    }
+
+    /// `f(filename)` returns the full path.
+    pub fn on_os_get_app_data_path_cat_filename(
+        &self,
+        f: impl FnMut(&str) -> PathBuf + Send + 'static,
+    ) -> &Self {
+        lock(&self.inner.hooks).os_get_app_data_path_cat_filename = Box::new(f);
+        self
+    }
+
+    /// `f(filename)` returns the full path.
+    pub fn on_os_get_local_app_data_path_cat_filename(
+        &self,
+        f: impl FnMut(&str) -> PathBuf + Send + 'static,
+    ) -> &Self {
+        lock(&self.inner.hooks).os_get_local_app_data_path_cat_filename = Box::new(f);
+        self
+    }
+
+    /// `f(user_data, name)`
+    pub fn on_ui_options_add_plugin_page(
+        &self,
+        f: impl FnMut(usize, &str) + Send + 'static,
+    ) -> &Self {
+        lock(&self.inner.hooks).ui_options_add_plugin_page = Box::new(f);
+        self
+    }
+
+    /// The named instance of Everything. Defaults to `None`, i.e. the default instance.
+    pub fn on_instance_name(&self, f: impl FnMut() -> Option<String> + Send + 'static) -> &Self {
+        lock(&self.inner.hooks).instance_name = Box::new(f);
+        self
+    }
+
+    /// `f(language)` returns the locale name, e.g. `en-US`. `language` is `None` for the UI language of the thread. Defaults to `en-US`.
+    pub fn on_language_name(&self, f: impl FnMut(Option<u16>) -> String + Send + 'static) -> &Self {
+        lock(&self.inner.hooks).language_name = Box::new(f);
+        self
+    }
+
+    /// The file name of the plugin DLL, i.e. the ini section name. Defaults to `TestPlugin.dll`.
+    pub fn on_plugin_dll_name(&self, f: impl FnMut() -> Option<String> + Send + 'static) -> &Self {
+        lock(&self.inner.hooks).plugin_dll_name = Box::new(f);
+        self
+    }
+
+    /// `f(caption, text)`. Replaces the default recording of [`Self::message_boxes()`].
+    pub fn on_message_box(&self, f: impl FnMut(&str, &str) + Send + 'static) -> &Self {
+        lock(&self.inner.hooks).message_box = Box::new(f);
+        self
+    }
+
+    /// A [`PluginHost`] backed by this host. Only valid while a [`Driver`] of this host exists.
+    pub fn host(&self) -> PluginHost {
+        PluginHost::new(Some(get_proc_address))
+    }
+}
+
+impl Default for FakeHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for FakeHost {
+    fn drop(&mut self) {
+        _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The host of the current [`Driver`].
+static CURRENT: Mutex<Option<Arc<Inner>>> = Mutex::new(None);
+/// Held by the current [`Driver`].
+static DRIVER: Mutex<()> = Mutex::new(());
+
+fn current() -> Arc<Inner> {
+    lock(&CURRENT)
+        .clone()
+        .expect("FakeHost API called without a Driver")
+}
+
+/// Call a Win32 hook of the current [`Driver`]'s host. `None` if no driver exists.
+fn os_hook<T>(f: impl FnOnce(&mut Hooks) -> T) -> Option<T> {
+    let inner = lock(&CURRENT).clone()?;
+    let mut hooks = lock(&inner.hooks);
+    Some(f(&mut hooks))
+}
+
+/// Replaces [`PluginHost::instance_name_from_main_thread()`].
+pub(crate) fn instance_name() -> Option<Option<String>> {
+    os_hook(|hooks| (hooks.instance_name)())
+}
+
+/// Replaces [`PluginHost::get_language_name()`] and [`PluginHost::get_thread_language_name()`].
+pub(crate) fn language_name(language: Option<u16>) -> Option<String> {
+    os_hook(|hooks| (hooks.language_name)(language))
+}
+
+/// Replaces [`crate::data::plugin_dll_name()`].
+pub(crate) fn plugin_dll_name() -> Option<Option<String>> {
+    os_hook(|hooks| (hooks.plugin_dll_name)())
+}
+
+/// Replaces `MessageBoxW`. `None` if not replaced.
+pub(crate) fn message_box(caption: &str, text: &str) -> Option<()> {
+    os_hook(|hooks| (hooks.message_box)(caption, text))
+}
+
+fn record(inner: &Inner, name: &'static str, args: Vec<String>) {
+    lock(&inner.calls).push(HostCall { name, args });
+}
+
+unsafe fn str_arg<'a>(s: *const sys::everything_plugin_utf8_t) -> &'a str {
+    if s.is_null() {
+        return "";
+    }
+    unsafe { CStr::from_ptr(s as _) }
+        .to_str()
+        .unwrap_or_default()
+}
+
+unsafe extern "system" fn get_proc_address(
+    name: *const sys::everything_plugin_utf8_t,
+) -> *mut c_void {
+    let name = unsafe { str_arg(name) };
+    let f: *const () = match name {
+        "utf8_buf_init" => utf8_buf_init as _,
+        "utf8_buf_kill" => utf8_buf_kill as _,
+        "utf8_buf_copy_utf8_string" => utf8_buf_copy_utf8_string as _,
+        "config_get_int_value" => config_get_int_value as _,
+        "config_set_int_value" => config_set_int_value as _,
+        "plugin_get_setting_string" => plugin_get_setting_string as _,
+        "plugin_set_setting_string" => plugin_set_setting_string as _,
+        "os_get_app_data_path_cat_filename" => os_get_app_data_path_cat_filename as _,
+        "os_get_local_app_data_path_cat_filename" => os_get_local_app_data_path_cat_filename as _,
+        "ui_options_add_plugin_page" => ui_options_add_plugin_page as _,
+        _ => ptr::null(),
+    };
+    f as _
+}
+
+const STACK_SIZE: usize = 260;
+
+unsafe fn buf_init(cbuf: *mut sys::everything_plugin_utf8_buf_t) {
+    unsafe {
+        let stack = &raw mut (*cbuf).stack;
+        (*stack)[0] = 0;
+        (*cbuf).buf = stack.cast();
+        (*cbuf).len = 0;
+        (*cbuf).size = STACK_SIZE;
+    }
+}
+
+unsafe fn buf_kill(cbuf: *mut sys::everything_plugin_utf8_buf_t) {
+    unsafe {
+        if (*cbuf).buf != (&raw mut (*cbuf).stack).cast() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                (*cbuf).buf,
+                (*cbuf).size,
+            )));
+        }
+        buf_init(cbuf);
+    }
+}
+
+unsafe fn buf_copy(cbuf: *mut sys::everything_plugin_utf8_buf_t, s: &[u8]) {
+    unsafe {
+        buf_kill(cbuf);
+        if s.len() >= STACK_SIZE {
+            let buf: Box<[u8]> = vec![0; s.len() + 1].into_boxed_slice();
+            (*cbuf).size = buf.len();
+            (*cbuf).buf = Box::into_raw(buf).cast();
+        }
+        ptr::copy_nonoverlapping(s.as_ptr(), (*cbuf).buf, s.len());
+        *(*cbuf).buf.add(s.len()) = 0;
+        (*cbuf).len = s.len();
+    }
+}
+
+unsafe extern "system" fn utf8_buf_init(cbuf: *mut sys::everything_plugin_utf8_buf_t) {
+    record(&current(), "utf8_buf_init", vec![]);
+    unsafe { buf_init(cbuf) };
+}
+
+unsafe extern "system" fn utf8_buf_kill(cbuf: *mut sys::everything_plugin_utf8_buf_t) {
+    record(&current(), "utf8_buf_kill", vec![]);
+    unsafe { buf_kill(cbuf) };
+}
+
+unsafe extern "system" fn utf8_buf_copy_utf8_string(
+    cbuf: *mut sys::everything_plugin_utf8_buf_t,
+    s: *const sys::everything_plugin_utf8_t,
+) {
+    let s = unsafe { str_arg(s) };
+    record(&current(), "utf8_buf_copy_utf8_string", vec![s.into()]);
+    unsafe { buf_copy(cbuf, s.as_bytes()) };
+}
+
+unsafe extern "system" fn config_get_int_value(name: *const sys::everything_plugin_utf8_t) -> i32 {
+    let name = unsafe { str_arg(name) };
+    let inner = current();
+    record(&inner, "config_get_int_value", vec![name.into()]);
+    (lock(&inner.hooks).config_get_int_value)(name)
+}
+
+unsafe extern "system" fn config_set_int_value(
+    name: *const sys::everything_plugin_utf8_t,
+    value: i32,
+) -> i32 {
+    let name = unsafe { str_arg(name) };
+    let inner = current();
+    record(
+        &inner,
+        "config_set_int_value",
+        vec![name.into(), value.to_string()],
+    );
+    (lock(&inner.hooks).config_set_int_value)(name, value)
+}
+
+unsafe extern "system" fn plugin_get_setting_string(
+    _sorted_list: *mut c_void,
+    name: *const sys::everything_plugin_utf8_t,
+    current_string: *mut sys::everything_plugin_utf8_t,
+) -> *mut sys::everything_plugin_utf8_t {
+    let name = unsafe { str_arg(name) };
+    let inner = current();
+    record(&inner, "plugin_get_setting_string", vec![name.into()]);
+    let value = (lock(&inner.hooks).plugin_get_setting_string)(name);
+    match value.and_then(|value| CString::new(value).ok()) {
+        Some(value) => {
+            let ptr = value.as_ptr() as *mut _;
+            lock(&inner.strings).push(value);
+            ptr
+        }
+        None => current_string,
+    }
+}
+
+unsafe extern "system" fn plugin_set_setting_string(
+    _output_stream: sys::everything_plugin_output_stream_t,
+    name: *const sys::everything_plugin_utf8_t,
+    value: *const sys::everything_plugin_utf8_t,
+) {
+    let (name, value) = unsafe { (str_arg(name), str_arg(value)) };
+    let inner = current();
+    record(
+        &inner,
+        "plugin_set_setting_string",
+        vec![name.into(), value.into()],
+    );
+    (lock(&inner.hooks).plugin_set_setting_string)(name, value);
+}
+
+unsafe extern "system" fn os_get_app_data_path_cat_filename(
+    filename: *const sys::everything_plugin_utf8_t,
+    cbuf: *mut sys::everything_plugin_utf8_buf_t,
+) {
+    let filename = unsafe { str_arg(filename) };
+    let inner = current();
+    record(
+        &inner,
+        "os_get_app_data_path_cat_filename",
+        vec![filename.into()],
+    );
+    let path = (lock(&inner.hooks).os_get_app_data_path_cat_filename)(filename);
+    unsafe { buf_copy(cbuf, path.to_string_lossy().as_bytes()) };
+}
+
+unsafe extern "system" fn os_get_local_app_data_path_cat_filename(
+    filename: *const sys::everything_plugin_utf8_t,
+    cbuf: *mut sys::everything_plugin_utf8_buf_t,
+) {
+    let filename = unsafe { str_arg(filename) };
+    let inner = current();
+    record(
+        &inner,
+        "os_get_local_app_data_path_cat_filename",
+        vec![filename.into()],
+    );
+    let path = (lock(&inner.hooks).os_get_local_app_data_path_cat_filename)(filename);
+    unsafe { buf_copy(cbuf, path.to_string_lossy().as_bytes()) };
+}
+
+unsafe extern "system" fn ui_options_add_plugin_page(
+    _add_custom_page: *mut c_void,
+    user_data: *mut c_void,
+    name: *const sys::everything_plugin_utf8_t,
+) -> *mut c_void {
+    let name = unsafe { str_arg(name) };
+    let inner = current();
+    record(
+        &inner,
+        "ui_options_add_plugin_page",
+        vec![(user_data as usize).to_string(), name.into()],
+    );
+    (lock(&inner.hooks).ui_options_add_plugin_page)(user_data as usize, name);
+    ptr::null_mut()
+}
+
+/// A headless options page for [`crate::ui::OptionsPage`]'s `load`, which can be loaded and saved by [`Driver`].
+///
+/// On save, `f` edits the config and returns whether it is changed. Errors of [`PluginApp::validate()`] are logged.
+///
+/// ## Example
+/// ```ignore
+/// OptionsPage::builder()
+///     .name("Test Plugin")
+///     .load(testing::options_page(|config: &mut Config| {
+///         config.s = "Hello".into();
+///         true
+///     }))
+///     .build()
+/// ```
+pub fn options_page<A: PluginApp>(
+    f: impl FnMut(&mut A::Config) -> bool + Send + 'static,
+) -> impl FnMut(OptionsPageLoadArgs) -> PageHandle<A> + 'static {
+    let f = Arc::new(Mutex::new(f));
+    move |_args| {
+        let f = f.clone();
+        PageHandle::headless(move |msg| match msg {
+            OptionsPageMessage::Save(config, tx) => {
+                if (lock(&f))(config) {
+                    _ = tx.send(config);
+                }
+            }
+            OptionsPageMessage::Invalid(errors) => debug!(?errors, "Options page invalid"),
+        })
+    }
+}
+
+/// Sends `PM_*` messages to a [`PluginHandler`] with a [`FakeHost`].
+///
+/// Waits for other drivers to be dropped when created.
+pub struct Driver<'a, A: PluginApp> {
+    handler: &'a PluginHandler<A>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl<'a, A: PluginApp> Driver<'a, A> {
+    pub fn new(handler: &'a PluginHandler<A>, host: &FakeHost) -> Self {
+        let guard = lock(&DRIVER);
+        *lock(&CURRENT) = Some(host.inner.clone());
+        Self {
+            handler,
+            _guard: guard,
+        }
+    }
+
+    /// Send a message, as `everything_plugin_proc()` generated by [`crate::plugin_main!`].
+    pub fn send(&self, msg: u32, data: *mut c_void) -> *mut c_void {
+        debug!(msg = message_name(msg), ?data, "Driver send");
+        PluginHandler::<A>::handle_init_i18n(msg, data);
+        self.handler.handle(msg, data)
+    }
+
+    /// A non-null placeholder for the setting list and output stream, which [`FakeHost`] ignores.
+    fn placeholder() -> *mut c_void {
+        NonNull::dangling().as_ptr()
+    }
+
+    pub fn init(&self) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_INIT, get_proc_address as _)
+    }
+
+    pub fn start(&self) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_START, Self::placeholder())
+    }
+
+    pub fn stop(&self) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_STOP, ptr::null_mut())
+    }
+
+    pub fn kill(&self) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_KILL, ptr::null_mut())
+    }
+
+    pub fn uninstall(&self) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_UNINSTALL, ptr::null_mut())
+    }
+
+    pub fn save_settings(&self) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_SAVE_SETTINGS, Self::placeholder())
+    }
+
+    /// Options pages are added to [`FakeHost::options_pages()`], and can be loaded with [`Self::load_options_page()`].
+    pub fn add_options_pages(&self) -> *mut c_void {
+        self.send(
+            sys::EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES,
+            Self::placeholder(),
+        )
+    }
+
+    /// Load the `index`th options page, which must be made with [`options_page()`].
+    pub fn load_options_page(&self, index: usize) -> *mut c_void {
+        let mut data = sys::everything_plugin_load_options_page_s {
+            user_data: index as _,
+            page_hwnd: ptr::null_mut(),
+            tooltip_hwnd: ptr::null_mut(),
+        };
+        self.send(
+            sys::EVERYTHING_PLUGIN_PM_LOAD_OPTIONS_PAGE,
+            &raw mut data as _,
+        )
+    }
+
+    /// Save the `index`th options page, as when OK or Apply is clicked. Everything saves all loaded pages in order.
+    pub fn save_options_page(&self, index: usize) -> *mut c_void {
+        let mut data = sys::everything_plugin_save_options_page_s {
+            user_data: index as _,
+            page_hwnd: ptr::null_mut(),
+            enable_apply: 0,
+        };
+        self.send(
+            sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE,
+            &raw mut data as _,
+        )
+    }
+
+    pub fn kill_options_page(&self, index: usize) -> *mut c_void {
+        self.send(sys::EVERYTHING_PLUGIN_PM_KILL_OPTIONS_PAGE, index as _)
+    }
+
+    /// Metadata messages, in the order Everything sends them when loading plugins.
+    pub fn metadata(&self) {
+        for msg in [
+            sys::EVERYTHING_PLUGIN_PM_GET_PLUGIN_VERSION,
+            sys::EVERYTHING_PLUGIN_PM_GET_NAME,
+            sys::EVERYTHING_PLUGIN_PM_GET_DESCRIPTION,
+            sys::EVERYTHING_PLUGIN_PM_GET_AUTHOR,
+            sys::EVERYTHING_PLUGIN_PM_GET_VERSION,
+            sys::EVERYTHING_PLUGIN_PM_GET_LINK,
+        ] {
+            self.send(msg, ptr::null_mut());
+        }
+    }
+
+    /// `PM_INIT`, metadata and `PM_START`, as when Everything starts.
+    pub fn startup(&self) {
+        self.init();
+        self.metadata();
+        self.start();
+    }
+
+    /// `PM_SAVE_SETTINGS`, `PM_STOP` and `PM_KILL`, as when Everything exits.
+    pub fn shutdown(&self) {
+        self.save_settings();
+        self.stop();
+        self.kill();
+    }
+
+    /// `PM_STOP`, `PM_UNINSTALL` and `PM_KILL`, as when the plugin is uninstalled in the options.
+    pub fn uninstall_sequence(&self) {
+        self.stop();
+        self.uninstall();
+        self.kill();
+    }
+}
+
+impl<A: PluginApp> Drop for Driver<'_, A> {
+    fn drop(&mut self) {
+        *lock(&CURRENT) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::ui::OptionsPage;
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    #[serde(default)]
+    struct Config {
+        s: String,
+        n: u32,
+    }
+
+    /// The number of apps created.
+    static NEWS: AtomicUsize = AtomicUsize::new(0);
+    /// The config passed to [`PluginApp::uninstall()`].
+    static UNINSTALLED: Mutex<Option<Config>> = Mutex::new(None);
+
+    struct App {
+        config: Config,
+    }
+
+    impl PluginApp for App {
+        type Config = Config;
+
+        fn new(config: Option<Self::Config>) -> Self {
+            NEWS.fetch_add(1, Ordering::SeqCst);
+            Self {
+                config: config.unwrap_or_default(),
+            }
+        }
+
+        fn uninstall(config: Self::Config) {
+            *lock(&UNINSTALLED) = Some(config);
+        }
+
+        fn config(&self) -> &Self::Config {
+            &self.config
+        }
+
+        fn into_config(self) -> Self::Config {
+            self.config
+        }
+
+        fn validate(config: &Self::Config) -> Result<(), Vec<crate::data::FieldError>> {
+            match config.s.as_str() {
+                "invalid" => Err(vec![crate::data::FieldError::new("s", "invalid")]),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    fn saved(host: &FakeHost) -> Config {
+        serde_json::from_str(&host.setting("_").unwrap()).unwrap()
+    }
+
+    fn handler() -> PluginHandler<App> {
+        PluginHandler::builder().name("Test Plugin").build()
+    }
+
+    #[test]
+    fn startup_save_shutdown() {
+        let host = FakeHost::new();
+        host.set_setting("_", r#"{"s":"Hi"}"#);
+        let handler = handler();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        assert_eq!(handler.state(), crate::lifecycle::LifecycleState::Started);
+        assert_eq!(handler.with_config(|config| config.s.clone()), "Hi");
+        driver.shutdown();
+        assert_eq!(handler.state(), crate::lifecycle::LifecycleState::Killed);
+
+        assert_eq!(
+            saved(&host),
+            Config {
+                s: "Hi".into(),
+                n: 0
+            }
+        );
+    }
+
+    #[test]
+    fn uninstall() {
+        let host = FakeHost::new();
+        host.set_setting("_", r#"{"s":"Hi"}"#);
+        let handler = handler();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.stop();
+        driver.uninstall();
+        assert_eq!(lock(&UNINSTALLED).take().unwrap().s, "Hi");
+        // Cleared instead of saved
+        driver.save_settings();
+        driver.kill();
+        assert_eq!(host.setting("_").as_deref(), Some(""));
+        drop(driver);
+
+        // Not started, with the default config as the ini section isn't readable
+        let handler = self::handler();
+        let driver = Driver::new(&handler, &host);
+        driver.init();
+        driver.uninstall();
+        assert_eq!(lock(&UNINSTALLED).take(), Some(Config::default()));
+    }
+
+    #[test]
+    fn win32_hooks() {
+        let host = FakeHost::new();
+        host.on_instance_name(|| Some("1.5a".into()))
+            .set_setting("_", "{");
+        let handler = handler();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        assert_eq!(handler.instance_name(), Some("1.5a"));
+        assert_eq!(handler.get_language_name(), "en-US");
+
+        // Invalid config is replaced by the default one
+        assert_eq!(
+            handler.with_config(|config| config.clone()),
+            Config::default()
+        );
+        let message_boxes = host.message_boxes();
+        assert_eq!(message_boxes.len(), 1);
+        assert_eq!(message_boxes[0].0, "Test Plugin");
+        driver.shutdown();
+    }
+
+    fn handler_with_pages() -> PluginHandler<App> {
+        PluginHandler::builder()
+            .name("Test Plugin")
+            .options_pages(vec![
+                OptionsPage::builder()
+                    .name("A")
+                    .load(options_page(|config: &mut Config| {
+                        config.s = "A".into();
+                        true
+                    }))
+                    .build(),
+                OptionsPage::builder()
+                    .name("B")
+                    .load(options_page(|config: &mut Config| {
+                        config.n += 1;
+                        true
+                    }))
+                    .build(),
+            ])
+            .build()
+    }
+
+    #[test]
+    fn save_options_pages() {
+        let host = FakeHost::new();
+        let handler = handler_with_pages();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.add_options_pages();
+        assert_eq!(host.options_pages(), ["A", "B"]);
+        driver.load_options_page(0);
+        driver.load_options_page(1);
+
+        let news = NEWS.load(Ordering::SeqCst);
+        driver.save_options_page(0);
+        // Not applied until all loaded pages are saved
+        assert_eq!(NEWS.load(Ordering::SeqCst), news);
+        assert!(handler.pending_config().is_some());
+        driver.save_options_page(1);
+        assert_eq!(NEWS.load(Ordering::SeqCst), news + 1);
+        assert!(handler.pending_config().is_none());
+        assert_eq!(
+            handler.with_config(|config| config.clone()),
+            Config {
+                s: "A".into(),
+                n: 1
+            }
+        );
+
+        // Apply again
+        driver.save_options_page(0);
+        driver.save_options_page(1);
+        assert_eq!(NEWS.load(Ordering::SeqCst), news + 2);
+        assert_eq!(handler.with_config(|config| config.n), 2);
+
+        driver.kill_options_page(0);
+        driver.kill_options_page(1);
+        driver.shutdown();
+        assert_eq!(
+            saved(&host),
+            Config {
+                s: "A".into(),
+                n: 2
+            }
+        );
+    }
+
+    #[test]
+    fn save_loaded_options_page() {
+        let host = FakeHost::new();
+        let handler = handler_with_pages();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.add_options_pages();
+        // Only loaded pages are saved
+        driver.load_options_page(1);
+
+        let news = NEWS.load(Ordering::SeqCst);
+        driver.save_options_page(1);
+        assert_eq!(NEWS.load(Ordering::SeqCst), news + 1);
+        assert_eq!(
+            handler.with_config(|config| config.clone()),
+            Config { s: "".into(), n: 1 }
+        );
+
+        driver.kill_options_page(1);
+        driver.shutdown();
+    }
+
+    #[test]
+    fn save_rejected_options_page() {
+        let host = FakeHost::new();
+        host.set_setting("_", r#"{"s":"Hi"}"#);
+        let handler: PluginHandler<App> = PluginHandler::builder()
+            .name("Test Plugin")
+            .options_pages(vec![
+                OptionsPage::builder()
+                    .name("A")
+                    .load(options_page(|config: &mut Config| {
+                        config.s = "invalid".into();
+                        true
+                    }))
+                    .build(),
+            ])
+            .build();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.add_options_pages();
+        driver.load_options_page(0);
+
+        // The old config is kept
+        assert!(driver.save_options_page(0).is_null());
+        assert_eq!(handler.with_config(|config| config.s.clone()), "Hi");
+
+        driver.kill_options_page(0);
+        driver.shutdown();
+        assert_eq!(saved(&host).s, "Hi");
+    }
+
+    #[test]
+    fn store_paths() {
+        let host = FakeHost::new();
+        host.on_plugin_dll_name(|| Some("Renamed.dll".into()));
+        let handler: PluginHandler<App> = PluginHandler::builder()
+            .name("Test Plugin")
+            .config_store(crate::data::store::PluginsJson)
+            .build();
+
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.shutdown();
+
+        let json = fs::read_to_string(host.dir().join("Roaming/plugins.json")).unwrap();
+        let plugins: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(plugins.get("Renamed").is_some(), "{json}");
+        drop(driver);
+
+        let handler: PluginHandler<App> = PluginHandler::builder()
+            .name("Test Plugin")
+            .config_store(crate::data::store::ConfigFile::default())
+            .build();
+        let driver = Driver::new(&handler, &host);
+        driver.startup();
+        driver.shutdown();
+        // In the config dir instead
+        assert!(
+            host.dir()
+                .join("Roaming/Plugins/Test Plugin/config.json")
+                .exists()
+        );
+    }
+}
@@ -6,6 +6,7 @@
 //! - No dark mode support
 
 use std::{
+    borrow::Cow,
     cell::UnsafeCell,
     ffi::{CString, c_void},
     fmt::Debug,
@@ -14,7 +15,7 @@ use std::{
 
 use bon::Builder;
 use futures_channel::mpsc;
-use tracing::{debug, trace, warn};
+use tracing::{debug, error, trace, warn};
 use windows_sys::Win32::{
     Foundation::HWND,
     UI::WindowsAndMessaging::{
@@ -23,7 +24,9 @@ use windows_sys::Win32::{
     },
 };
 
-use crate::{PluginApp, PluginHandler, PluginHost, sys};
+use crate::{
+    PluginApp, PluginHandler, PluginHost, data::FieldError, localization::LocalizedText, sys,
+};
 
 #[cfg(feature = "winio")]
 pub mod winio;
@@ -33,8 +36,10 @@ pub mod winio;
 #[derive(Builder)]
 pub struct OptionsPage<A: PluginApp> {
     /// If conflicts with other plugins, Everything will append a " (plugin.dll)" suffix.
+    ///
+    /// Resolved on each [`sys::EVERYTHING_PLUGIN_PM_ADD_OPTIONS_PAGES`], see [`LocalizedText`].
     #[builder(into)]
-    name: String,
+    name: LocalizedText,
     #[builder(with = |x: impl FnMut(OptionsPageLoadArgs) -> PageHandle<A> + 'static| UnsafeCell::new(Box::new(x)))]
     load: UnsafeCell<Box<dyn FnMut(OptionsPageLoadArgs) -> PageHandle<A>>>,
     #[builder(default)]
@@ -42,6 +47,11 @@ pub struct OptionsPage<A: PluginApp> {
 }
 
 impl<A: PluginApp> OptionsPage<A> {
+    /// In the current locale.
+    pub fn name(&self) -> Cow<'_, str> {
+        self.name.get()
+    }
+
     fn load_mut(&self) -> &mut dyn FnMut(OptionsPageLoadArgs) -> PageHandle<A> {
         unsafe { &mut *self.load.get() }
     }
@@ -53,6 +63,20 @@ impl<A: PluginApp> OptionsPage<A> {
     fn handle_mut(&self) -> &mut Option<PageHandle<A>> {
         unsafe { &mut *self.handle.get() }
     }
+
+    /// Resize the loaded page, in physical pixels.
+    pub(crate) fn resize(&self, size: (i32, i32)) {
+        if let Some(handle) = self.handle() {
+            _ = handle
+                .tx
+                .unbounded_send(OptionsPageInternalMessage::Size(size));
+        }
+    }
+
+    /// Loaded and not killed yet, i.e. will be sent [`sys::EVERYTHING_PLUGIN_PM_SAVE_OPTIONS_PAGE`].
+    pub(crate) fn is_loaded(&self) -> bool {
+        self.handle().is_some()
+    }
 }
 
 #[derive(Debug)]
@@ -96,17 +120,27 @@ pub enum OptionsPageMessage<A: PluginApp> {
     ///
     /// Just drop the `tx` if there is no need to save the config (i.e. no changes).
     ///
+    /// The config may already contain the changes of other pages saved earlier, which are applied together after the last page is saved.
+    ///
     /// Note [`PluginHandler::app`] is not available during saving.
     Save(
         &'static mut A::Config,
         std::sync::mpsc::SyncSender<&'static mut A::Config>,
     ),
+    /// The saved config is rejected by [`PluginApp::validate()`] and not applied.
+    ///
+    /// The errors should be displayed to the user.
+    Invalid(Vec<FieldError>),
 }
 
 impl<A: PluginApp> Debug for OptionsPageMessage<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             OptionsPageMessage::Save(_config, _tx) => write!(f, "OptionsPageMessage::Save"),
+            OptionsPageMessage::Invalid(errors) => f
+                .debug_tuple("OptionsPageMessage::Invalid")
+                .field(errors)
+                .finish(),
         }
     }
 }
@@ -117,6 +151,50 @@ pub struct PageHandle<A: PluginApp> {
     tx: mpsc::UnboundedSender<OptionsPageInternalMessage<A>>,
 }
 
+impl<A: PluginApp> PageHandle<A> {
+    /// A page without a window, handling messages with `f` on a new thread. See [`crate::testing::options_page()`].
+    #[cfg(feature = "testing")]
+    pub(crate) fn headless(mut f: impl FnMut(OptionsPageMessage<A>) + Send + 'static) -> Self {
+        use std::{
+            pin::pin,
+            sync::Arc,
+            task::{Context, Poll, Wake, Waker},
+            thread::{self, Thread},
+        };
+
+        use futures_util::StreamExt;
+
+        struct Unpark(Thread);
+
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let (tx, mut rx) = mpsc::unbounded();
+        let thread_handle = thread::spawn(move || {
+            let waker = Waker::from(Arc::new(Unpark(thread::current())));
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                let msg = match pin!(rx.next()).poll(&mut cx) {
+                    Poll::Ready(msg) => msg,
+                    Poll::Pending => {
+                        thread::park();
+                        continue;
+                    }
+                };
+                match msg {
+                    Some(OptionsPageInternalMessage::Msg(msg)) => f(msg),
+                    Some(OptionsPageInternalMessage::Size(_)) => (),
+                    Some(OptionsPageInternalMessage::Kill) | None => break,
+                }
+            }
+        });
+        Self { thread_handle, tx }
+    }
+}
+
 impl<A: PluginApp> PluginHandler<A> {
     pub fn add_options_pages(&self, data: *mut c_void) -> *mut c_void {
         debug!("Plugin add options pages");
@@ -125,7 +203,7 @@ impl<A: PluginApp> PluginHandler<A> {
         } else {
             for (i, page) in self.options_pages.iter().enumerate() {
                 self.host()
-                    .ui_options_add_plugin_page(data, i as _, &page.name);
+                    .ui_options_add_plugin_page(data, i as _, &page.name());
             }
             1 as _
         }
@@ -190,8 +268,14 @@ impl<A: PluginApp> PluginHandler<A> {
 
                 let (tx, rx) = std::sync::mpsc::sync_channel(1);
 
-                let mut config = self.app_into_config();
+                let Some(mut config) = self.config_take_pending() else {
+                    warn!("App not inited, can't save");
+                    return 0 as _;
+                };
+                let backup = self.config_backup(&config);
                 let config_static: &'static mut A::Config = unsafe { mem::transmute(&mut config) };
+                let mut changed = false;
+                let mut rejected = false;
                 match handle
                     .tx
                     .unbounded_send(OptionsPageMessage::Save(config_static, tx).into())
@@ -199,10 +283,35 @@ impl<A: PluginApp> PluginHandler<A> {
                     Ok(()) => {
                         if let Ok(_config) = rx.recv() {
                             debug!(?config, "Options page config");
-                            self.app_new(Some(config));
+                            changed = true;
+                            if let Err(errors) = A::validate(&config) {
+                                warn!(?errors, "Options page config invalid");
+                                _ = handle
+                                    .tx
+                                    .unbounded_send(OptionsPageMessage::Invalid(errors).into());
+                                changed = false;
+                                rejected = true;
+                                match backup {
+                                    Some(old) => config = old,
+                                    None => {
+                                        // Never apply a rejected config
+                                        error!("Options page config restore error");
+                                        self.config_discard(config);
+                                        self.options_message.set(OptionsMessage::EnableApply(true));
+                                        return 0 as _;
+                                    }
+                                }
+                            }
                         }
                     }
-                    Err(_) => (),
+                    Err(_) => warn!("Options page is closed, can't save"),
+                }
+                // Defer even if failed to save, otherwise the app is lost
+                self.config_defer(config, changed);
+                if rejected {
+                    // Keep Apply enabled to retry after fixing the errors
+                    self.options_message.set(OptionsMessage::EnableApply(true));
+                    return 0 as _;
                 }
             }
             None => warn!("Options page handle is None, can't save"),
@@ -274,12 +383,7 @@ impl<A: PluginApp> PluginHandler<A> {
                 );
             }
             WM_SIZE => {
-                if let Some(handle) = page.handle() {
-                    _ = handle.tx.unbounded_send(OptionsPageInternalMessage::Size((
-                        (l_param & 0xFFFF) as i32,
-                        (l_param >> 16) as i32,
-                    )));
-                }
+                page.resize(((l_param & 0xFFFF) as i32, (l_param >> 16) as i32));
             }
             WM_PARENTNOTIFY => {
                 debug!(wParam = data.wParam, "WM_PARENTNOTIFY");
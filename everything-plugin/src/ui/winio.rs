@@ -14,6 +14,11 @@ use crate::ui::{OptionsPageInternalMessage, OptionsPageLoadArgs, PageHandle};
 
 pub use winio;
 
+pub mod form;
+#[cfg(feature = "tracing")]
+pub mod log;
+pub mod toolbar;
+
 pub mod prelude {
     pub use super::{super::OptionsPageMessage, OptionsPageInit};
     pub use crate::PluginApp;
@@ -0,0 +1,165 @@
+//! Runtime support for `#[derive(OptionsForm)]` and `#[derive(OptionsEnum)]`, which generate an options page from the config struct.
+//!
+//! Widgets are mapped by field type:
+//! - `bool`: [`CheckBox`]
+//! - `String`: [`Edit`]
+//! - Integers and floats: [`Edit`], parsed on save with [`parse_number()`]
+//! - Fields with `#[options_form(combo)]`: [`ComboBox`], with [`OptionsEnum`]
+//!
+//! Other types (including `Option<T>` and type aliases) are rejected at compile time and must be `skip`ped.
+//!
+//! ## Example
+//! ```ignore
+//! use everything_plugin::ui::winio::form::{OptionsEnum, OptionsForm};
+//!
+//! #[derive(OptionsForm, Serialize, Deserialize, Debug, Default)]
+//! #[options_form(app = App)]
+//! pub struct Config {
+//!     #[options_form(label = "Enable")]
+//!     enabled: bool,
+//!     #[options_form(label = t!("options.message"), help = "Shown on start", enabled_by = enabled)]
+//!     s: String,
+//!     #[options_form(group = "Advanced", enabled_by = enabled)]
+//!     port: u16,
+//!     #[options_form(group = "Advanced", enabled_by = enabled, combo)]
+//!     mode: Mode,
+//! }
+//!
+//! #[derive(OptionsEnum, Serialize, Deserialize, Debug, Default)]
+//! pub enum Mode {
+//!     #[default]
+//!     A,
+//!     #[options_form(label = "Mode B")]
+//!     B,
+//! }
+//!
+//! // Generates `ConfigForm`
+//! OptionsPage::builder()
+//!     .name("Test Plugin")
+//!     .load(ui::winio::spawn::<ConfigForm>)
+//!     .build()
+//! ```
+//!
+//! Struct attributes:
+//! - `app = App` (required): The [`PluginApp`]
+//! - `handler = expr`: The [`crate::PluginHandler`]. Defaults to `App::handler()` from `#[everything_plugin]`.
+//! - `name = ConfigForm`: The generated component. Defaults to the struct name with a `Form` suffix.
+//!
+//! Field attributes:
+//! - `label = expr`: A string, or `t!("key")` for i18n. Defaults to the field name.
+//! - `help = expr`: Tooltip text
+//! - `group = expr`: Fields of the same group are put under a heading, after ungrouped fields
+//! - `enabled_by = field`: Only enabled when the `bool` field before it is checked (and enabled)
+//! - `combo`: Shown as a [`ComboBox`], the type must implement [`OptionsEnum`]
+//! - `skip`: Not shown, kept as is
+
+use std::{fmt::Display, ptr, str::FromStr};
+
+use windows_sys::Win32::{
+    Foundation::HWND,
+    System::LibraryLoader::GetModuleHandleW,
+    UI::{
+        Controls::{
+            TOOLTIPS_CLASSW, TTF_IDISHWND, TTF_SUBCLASS, TTM_ADDTOOLW, TTM_SETMAXTIPWIDTH,
+            TTS_ALWAYSTIP, TTS_NOPREFIX, TTTOOLINFOW,
+        },
+        WindowsAndMessaging::{
+            CW_USEDEFAULT, CreateWindowExW, DestroyWindow, SendMessageW, WS_POPUP,
+        },
+    },
+};
+use winio::prelude::*;
+
+use crate::data::FieldError;
+
+#[cfg(feature = "macros")]
+pub use everything_plugin_macros::{OptionsEnum, OptionsForm};
+
+/// A fieldless enum shown as a [`ComboBox`]. Usually derived with `#[derive(OptionsEnum)]`.
+pub trait OptionsEnum: Sized {
+    /// In the order of [`Self::index()`].
+    fn labels() -> Vec<String>;
+
+    fn index(&self) -> usize;
+
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+/// Parse a number field.
+pub fn parse_number<T: FromStr<Err: Display>>(field: &str, text: &str) -> Result<T, FieldError> {
+    text.trim()
+        .parse()
+        .map_err(|e| FieldError::new(field, format!("invalid number \"{text}\": {e}")))
+}
+
+/// Show [`FieldError`]s from number parsing or [`crate::PluginApp::validate()`].
+pub async fn show_errors(window: &Window, errors: &[FieldError]) {
+    let errors = errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    MessageBox::new()
+        .title("Invalid options")
+        .message(errors)
+        .style(MessageBoxStyle::Warning)
+        .buttons(MessageBoxButton::Ok)
+        .show(window)
+        .await;
+}
+
+/// A Win32 tooltip control for the `help` texts.
+///
+/// TODO: Use `tooltip_hwnd` from Everything
+pub struct Tooltip {
+    hwnd: HWND,
+    parent: HWND,
+}
+
+impl Tooltip {
+    pub fn new(window: &Window) -> Self {
+        let parent = window.as_raw_window().as_win32();
+        let hwnd = unsafe {
+            CreateWindowExW(
+                0,
+                TOOLTIPS_CLASSW,
+                ptr::null(),
+                WS_POPUP | TTS_ALWAYSTIP | TTS_NOPREFIX,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                parent,
+                ptr::null_mut(),
+                GetModuleHandleW(ptr::null()),
+                ptr::null(),
+            )
+        };
+        // Enable multiline
+        unsafe { SendMessageW(hwnd, TTM_SETMAXTIPWIDTH, 0, 400) };
+        Self { hwnd, parent }
+    }
+
+    pub fn add(&self, widget: &impl AsRawWidget, text: impl AsRef<str>) {
+        if self.hwnd.is_null() {
+            return;
+        }
+        let mut text: Vec<u16> = text.as_ref().encode_utf16().chain([0]).collect();
+        let mut info: TTTOOLINFOW = unsafe { std::mem::zeroed() };
+        info.cbSize = size_of::<TTTOOLINFOW>() as _;
+        info.uFlags = TTF_IDISHWND | TTF_SUBCLASS;
+        info.hwnd = self.parent;
+        info.uId = widget.as_raw_widget().as_win32() as usize;
+        // Copied by the control
+        info.lpszText = text.as_mut_ptr();
+        unsafe { SendMessageW(self.hwnd, TTM_ADDTOOLW, 0, &raw const info as _) };
+    }
+}
+
+impl Drop for Tooltip {
+    fn drop(&mut self) {
+        if !self.hwnd.is_null() {
+            unsafe { DestroyWindow(self.hwnd) };
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! A ready-made widget for changing the log level at runtime, see [`crate::log::set_level()`].
+//!
+//! ## Example
+//! ```ignore
+//! // init()
+//! let log_level = LogLevelWidget::new(&window);
+//!
+//! // start()
+//! start! {
+//!     sender, default: MainMessage::Noop,
+//!     self.log_level.combo => {
+//!         ComboBoxEvent::Select => MainMessage::LogLevel,
+//!     }
+//! }
+//!
+//! // update()
+//! MainMessage::LogLevel => {
+//!     self.log_level.apply();
+//!     false
+//! }
+//!
+//! // render(): lay out `self.log_level.label` and `self.log_level.combo`
+//! ```
+
+use tracing::{level_filters::LevelFilter, warn};
+use winio::prelude::*;
+
+use crate::log;
+
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::OFF,
+    LevelFilter::ERROR,
+    LevelFilter::WARN,
+    LevelFilter::INFO,
+    LevelFilter::DEBUG,
+    LevelFilter::TRACE,
+];
+
+pub struct LogLevelWidget {
+    pub label: Child<Label>,
+    pub combo: Child<ComboBox>,
+}
+
+impl LogLevelWidget {
+    /// The current level is selected.
+    pub fn new(window: &Child<Window>) -> Self {
+        Self::with_label(window, "Log level:")
+    }
+
+    /// `text` should be in the current locale, which options pages follow as they are recreated on each load.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// LogLevelWidget::with_label(&window, &t!("options.log_level"))
+    /// ```
+    pub fn with_label(window: &Child<Window>, text: &str) -> Self {
+        let mut label = Child::<Label>::init(window);
+        label.set_text(text);
+
+        let mut combo = Child::<ComboBox>::init(window);
+        for (i, level) in LEVELS.iter().enumerate() {
+            combo.insert(i, level.to_string());
+        }
+
+        let mut widget = Self { label, combo };
+        widget.sync();
+        widget
+    }
+
+    /// Select the current level.
+    pub fn sync(&mut self) {
+        let level = log::level();
+        self.combo
+            .set_selection(LEVELS.iter().position(|l| *l == level));
+    }
+
+    /// The selected level.
+    pub fn level(&self) -> Option<LevelFilter> {
+        self.combo.selection().and_then(|i| LEVELS.get(i).copied())
+    }
+
+    /// Apply the selected level. Should be called on [`ComboBoxEvent::Select`].
+    pub fn apply(&self) {
+        if let Some(level) = self.level()
+            && let Err(e) = log::set_level(level)
+        {
+            warn!(%e, "Log level");
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.label.set_enabled(enabled);
+        self.combo.set_enabled(enabled);
+    }
+}
@@ -0,0 +1,213 @@
+//! A ready-made Export…/Import…/Reset toolbar for options pages, see [`crate::data::transfer`].
+//!
+//! Import and Reset only fill the page with the new config. It is applied on Apply/OK like other changes, after [`crate::PluginApp::validate()`].
+//!
+//! Export writes the config built from the page's widgets, as the app may be recreated on the plugin thread meanwhile.
+//!
+//! Texts use Everything's own translations where available, and can be replaced with [`ConfigToolbar::with_texts()`].
+//!
+//! ## Example
+//! ```ignore
+//! // init()
+//! let toolbar = ConfigToolbar::new(&window);
+//!
+//! // start()
+//! start! {
+//!     sender, default: MainMessage::Noop,
+//!     self.toolbar.export => {
+//!         ButtonEvent::Click => MainMessage::Export,
+//!     },
+//!     self.toolbar.import => {
+//!         ButtonEvent::Click => MainMessage::Import,
+//!     },
+//!     self.toolbar.reset => {
+//!         ButtonEvent::Click => MainMessage::Reset,
+//!     }
+//! }
+//!
+//! // update()
+//! MainMessage::Export => {
+//!     // Built from the widgets, e.g. with the same code as `OptionsPageMessage::Save`
+//!     let config = self.config();
+//!     self.toolbar.export(&HANDLER, &self.window, &config).await;
+//!     false
+//! }
+//! MainMessage::Import => {
+//!     if let Some(config) = self.toolbar.import(&HANDLER, &self.window).await {
+//!         self.set_config(&config);
+//!     }
+//!     false
+//! }
+//! MainMessage::Reset => {
+//!     if let Some(config) = self.toolbar.reset::<App>(&self.window).await {
+//!         self.set_config(&config);
+//!     }
+//!     false
+//! }
+//!
+//! // render(): lay out `self.toolbar.export`, `self.toolbar.import` and `self.toolbar.reset`
+//! ```
+
+use bon::Builder;
+use tracing::{debug, warn};
+use winio::prelude::*;
+
+use crate::{
+    PluginApp, PluginHandler,
+    localization::{LocalizationId, LocalizedText},
+};
+
+/// See [`ConfigToolbar::with_texts()`].
+#[derive(Builder)]
+pub struct ConfigToolbarTexts {
+    #[builder(into, default = LocalizedText::fixed("Export..."))]
+    export: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Import..."))]
+    import: LocalizedText,
+    #[builder(into, default = LocalizedText::everything(LocalizationId::HttpServerRestoreDefaults, "Reset"))]
+    reset: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Export settings"))]
+    export_title: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Import settings"))]
+    import_title: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Reset settings"))]
+    reset_title: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Reset all settings to defaults?"))]
+    reset_message: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Failed to export settings"))]
+    export_error: LocalizedText,
+    #[builder(into, default = LocalizedText::fixed("Failed to import settings"))]
+    import_error: LocalizedText,
+    #[builder(into, default = LocalizedText::everything(LocalizationId::AllFiles, "All files"))]
+    all_files: LocalizedText,
+}
+
+impl Default for ConfigToolbarTexts {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+pub struct ConfigToolbar {
+    pub export: Child<Button>,
+    pub import: Child<Button>,
+    pub reset: Child<Button>,
+    texts: ConfigToolbarTexts,
+}
+
+impl ConfigToolbar {
+    pub fn new(window: &Child<Window>) -> Self {
+        Self::with_texts(window, Default::default())
+    }
+
+    /// ## Example
+    /// ```ignore
+    /// ConfigToolbar::with_texts(
+    ///     &window,
+    ///     ConfigToolbarTexts::builder()
+    ///         .export(localized!("options.export"))
+    ///         .import(localized!("options.import"))
+    ///         .build(),
+    /// )
+    /// ```
+    pub fn with_texts(window: &Child<Window>, texts: ConfigToolbarTexts) -> Self {
+        let mut export = Child::<Button>::init(window);
+        export.set_text(texts.export.get());
+
+        let mut import = Child::<Button>::init(window);
+        import.set_text(texts.import.get());
+
+        let mut reset = Child::<Button>::init(window);
+        reset.set_text(texts.reset.get());
+
+        Self {
+            export,
+            import,
+            reset,
+            texts,
+        }
+    }
+
+    fn file_box<A: PluginApp>(&self, handler: &PluginHandler<A>) -> FileBox {
+        let extension = handler.config_extension();
+        FileBox::new()
+            .filename(format!("{}.{extension}", handler.plugin_name()))
+            .add_filter((
+                format!("{} (*.{extension})", extension.to_uppercase()),
+                format!("*.{extension}"),
+            ))
+            .add_filter((format!("{} (*.*)", self.texts.all_files.get()), "*.*"))
+    }
+
+    /// Export the config of the page to a file chosen by the user. Should be called on the click of [`Self::export`].
+    ///
+    /// `config` should be built from the page's widgets, like on [`crate::ui::OptionsPageMessage::Save`].
+    pub async fn export<A: PluginApp>(
+        &self,
+        handler: &PluginHandler<A>,
+        window: &Window,
+        config: &A::Config,
+    ) {
+        let file_box = self.file_box(handler).title(self.texts.export_title.get());
+        let Some(path) = file_box.save(window).await else {
+            return;
+        };
+        if let Err(e) = handler.write_config(&path, config) {
+            warn!(?path, %e, "Config export error");
+            show_error(window, &self.texts.export_error.get(), &e.to_string()).await;
+        }
+    }
+
+    /// Read a config file chosen by the user. Should be called on the click of [`Self::import`].
+    ///
+    /// `None` if cancelled or failed, in which case the error is already shown.
+    pub async fn import<A: PluginApp>(
+        &self,
+        handler: &PluginHandler<A>,
+        window: &Window,
+    ) -> Option<A::Config> {
+        let file_box = self.file_box(handler).title(self.texts.import_title.get());
+        let path = file_box.open(window).await?;
+        match handler.read_config(&path) {
+            Ok(config) => {
+                debug!(?path, ?config, "Config read for import");
+                Some(config)
+            }
+            Err(e) => {
+                warn!(?path, %e, "Config import error");
+                show_error(window, &self.texts.import_error.get(), &e.to_string()).await;
+                None
+            }
+        }
+    }
+
+    /// The default config, after the user confirms. Should be called on the click of [`Self::reset`].
+    pub async fn reset<A: PluginApp>(&self, window: &Window) -> Option<A::Config>
+    where
+        A::Config: Default,
+    {
+        let response = MessageBox::new()
+            .title(self.texts.reset_title.get())
+            .message(self.texts.reset_message.get())
+            .buttons(MessageBoxButton::Yes | MessageBoxButton::No)
+            .show(window)
+            .await;
+        (response == MessageBoxResponse::Yes).then(Default::default)
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.export.set_enabled(enabled);
+        self.import.set_enabled(enabled);
+        self.reset.set_enabled(enabled);
+    }
+}
+
+async fn show_error(window: &Window, title: &str, message: &str) {
+    MessageBox::new()
+        .title(title)
+        .message(message)
+        .style(MessageBoxStyle::Error)
+        .buttons(MessageBoxButton::Ok)
+        .show(window)
+        .await;
+}
@@ -0,0 +1,86 @@
+use std::sync::Mutex;
+
+use everything_ipc::{TargetMachine, Version};
+
+use tracing::debug;
+
+use crate::{PluginHost, sys};
+
+impl PluginHost {
+    fn version_get_u32(&self, name: &str) -> u32 {
+        let version_get: unsafe extern "system" fn() -> sys::DWORD = unsafe { self.require(name) };
+        unsafe { version_get() as _ }
+    }
+
+    pub fn version_get_major(&self) -> u32 {
+        self.version_get_u32("version_get_major")
+    }
+
+    pub fn version_get_minor(&self) -> u32 {
+        self.version_get_u32("version_get_minor")
+    }
+
+    pub fn version_get_revision(&self) -> u32 {
+        self.version_get_u32("version_get_revision")
+    }
+
+    pub fn version_get_build(&self) -> u32 {
+        self.version_get_u32("version_get_build")
+    }
+
+    /// [`TargetMachine::Unknown`] if the API is unavailable in the running Everything.
+    pub fn version_get_target_machine(&self) -> TargetMachine {
+        // Not `try_get()`, which reports `Self::version()` that calls this
+        let version_get_target_machine: Option<unsafe extern "system" fn() -> sys::DWORD> =
+            unsafe { self.get("version_get_target_machine") };
+        let Some(version_get_target_machine) = version_get_target_machine else {
+            debug!("version_get_target_machine is unavailable");
+            return TargetMachine::Unknown;
+        };
+        let target_machine: u32 = unsafe { version_get_target_machine() } as _;
+        target_machine.into()
+    }
+
+    /// The version of Everything the plugin is running in.
+    ///
+    /// Same as [`everything_ipc::IpcWindow::get_version()`], but without IPC. See [`Self::version_with_target_machine()`].
+    pub fn version(&self) -> Version {
+        self.version_with_target_machine().0
+    }
+
+    /// Same as [`everything_ipc::IpcWindow::get_version_with_target_machine()`], but without IPC. Cached per host after the first call.
+    pub fn version_with_target_machine(&self) -> (Version, TargetMachine) {
+        /// Keyed by `get_proc_address`.
+        static VERSIONS: Mutex<Vec<(usize, (Version, TargetMachine))>> = Mutex::new(Vec::new());
+
+        let key = self.get_proc_address.map_or(0, |f| f as usize);
+        let cached = VERSIONS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, version)| *version);
+        if let Some(version) = cached {
+            return version;
+        }
+
+        let version = (
+            Version::new(
+                self.version_get_major(),
+                self.version_get_minor(),
+                self.version_get_revision(),
+                self.version_get_build(),
+            ),
+            self.version_get_target_machine(),
+        );
+        VERSIONS.lock().unwrap().push((key, version));
+        version
+    }
+
+    /// The plugin API version of Everything.
+    ///
+    /// See also [`sys::EVERYTHING_PLUGIN_VERSION`].
+    pub fn plugin_get_version(&self) -> u32 {
+        self.version_get_u32("plugin_get_version")
+    }
+}